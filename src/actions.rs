@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::result::Result;
+
+use c2pa::assertions::{c2pa_action, labels};
+use c2pa::Manifest;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::common::SimpleC2PAError;
+use crate::content_credentials::ContentCredentials;
+use crate::manifest_reader::AssertionInfo;
+
+const ACTIONS_V2_LABEL: &str = "c2pa.actions.v2";
+const DIGITAL_SOURCE_TYPE_BASE: &str = "http://cv.iptc.org/newscodes/digitalsourcetype/";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionType {
+    Created,
+    Opened,
+    Placed,
+    Edited,
+    Cropped,
+    Resized,
+    Orientation,
+    ColorAdjustments,
+    Filtered,
+    Drawing,
+    Removed,
+    Redacted,
+    Converted,
+    Transcoded,
+    Repackaged,
+    Produced,
+    Published,
+    /// Any other action, such as a vendor specific `com.example.action`.
+    Other {
+        label: String,
+    },
+}
+
+impl ActionType {
+    pub fn get_label(&self) -> &str {
+        match self {
+            ActionType::Created => c2pa_action::CREATED,
+            ActionType::Opened => c2pa_action::OPENED,
+            ActionType::Placed => c2pa_action::PLACED,
+            ActionType::Edited => c2pa_action::EDITED,
+            ActionType::Cropped => c2pa_action::CROPPED,
+            ActionType::Resized => c2pa_action::RESIZED,
+            ActionType::Orientation => c2pa_action::ORIENTATION,
+            ActionType::ColorAdjustments => c2pa_action::COLOR_ADJUSTMENTS,
+            ActionType::Filtered => c2pa_action::FILTERED,
+            ActionType::Drawing => c2pa_action::DRAWING,
+            ActionType::Removed => c2pa_action::REMOVED,
+            ActionType::Redacted => c2pa_action::REDACTED,
+            ActionType::Converted => c2pa_action::CONVERTED,
+            ActionType::Transcoded => c2pa_action::TRANSCODED,
+            ActionType::Repackaged => c2pa_action::REPACKAGED,
+            ActionType::Produced => c2pa_action::PRODUCED,
+            ActionType::Published => c2pa_action::PUBLISHED,
+            ActionType::Other { label } => label,
+        }
+    }
+}
+
+/// The IPTC digital source type of the content produced by an action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DigitalSourceType {
+    DigitalCapture,
+    ComputationalCapture,
+    NegativeFilm,
+    PositiveFilm,
+    Print,
+    HumanEdits,
+    CompositeCapture,
+    AlgorithmicallyEnhanced,
+    DigitalCreation,
+    DataDrivenMedia,
+    TrainedAlgorithmicMedia,
+    AlgorithmicMedia,
+    ScreenCapture,
+    VirtualRecording,
+    Composite,
+    CompositeSynthetic,
+    CompositeWithTrainedAlgorithmicMedia,
+    Other { uri: String },
+}
+
+impl DigitalSourceType {
+    pub fn get_uri(&self) -> String {
+        let name = match self {
+            DigitalSourceType::DigitalCapture => "digitalCapture",
+            DigitalSourceType::ComputationalCapture => "computationalCapture",
+            DigitalSourceType::NegativeFilm => "negativeFilm",
+            DigitalSourceType::PositiveFilm => "positiveFilm",
+            DigitalSourceType::Print => "print",
+            DigitalSourceType::HumanEdits => "humanEdits",
+            DigitalSourceType::CompositeCapture => "compositeCapture",
+            DigitalSourceType::AlgorithmicallyEnhanced => "algorithmicallyEnhanced",
+            DigitalSourceType::DigitalCreation => "digitalCreation",
+            DigitalSourceType::DataDrivenMedia => "dataDrivenMedia",
+            DigitalSourceType::TrainedAlgorithmicMedia => "trainedAlgorithmicMedia",
+            DigitalSourceType::AlgorithmicMedia => "algorithmicMedia",
+            DigitalSourceType::ScreenCapture => "screenCapture",
+            DigitalSourceType::VirtualRecording => "virtualRecording",
+            DigitalSourceType::Composite => "composite",
+            DigitalSourceType::CompositeSynthetic => "compositeSynthetic",
+            DigitalSourceType::CompositeWithTrainedAlgorithmicMedia => {
+                "compositeWithTrainedAlgorithmicMedia"
+            }
+            DigitalSourceType::Other { uri } => return uri.clone(),
+        };
+        format!("{}{}", DIGITAL_SOURCE_TYPE_BASE, name)
+    }
+}
+
+/// A region of the asset changed by an action.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionRegion {
+    /// A rectangle, in pixels from the top left corner.
+    Rectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+    /// A time range, as normal play time such as `"12.5"` or `"0:01:30"`.
+    Time { start: String, end: String },
+}
+
+impl ActionRegion {
+    fn to_json(&self) -> Value {
+        let range = match self {
+            ActionRegion::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            } => json!({
+                "type": "spatial",
+                "shape": {
+                    "type": "rectangle",
+                    "unit": "pixel",
+                    "origin": { "x": x, "y": y },
+                    "width": width,
+                    "height": height,
+                },
+            }),
+            ActionRegion::Time { start, end } => json!({
+                "type": "temporal",
+                "time": { "type": "npt", "start": start, "end": end },
+            }),
+        };
+        json!({ "region": [range] })
+    }
+}
+
+/// One entry of the `c2pa.actions` assertion.
+#[derive(Debug, Clone)]
+pub struct ActionData {
+    pub action: ActionType,
+    pub when: Option<DateTime<Utc>>,
+    pub software_agent: Option<String>,
+    pub parameters: HashMap<String, Value>,
+    pub changes: Vec<ActionRegion>,
+    /// Instance ID of the ingredient the action was performed on, as returned by
+    /// [`ContentCredentials::add_ingredient`].
+    pub ingredient_instance_id: Option<String>,
+    pub digital_source_type: Option<DigitalSourceType>,
+    /// Why the action was performed, such as `c2pa.PII.present` for a redaction.
+    pub reason: Option<String>,
+}
+
+impl ActionData {
+    pub fn new(action: ActionType) -> Self {
+        ActionData {
+            action,
+            when: None,
+            software_agent: None,
+            parameters: HashMap::new(),
+            changes: vec![],
+            ingredient_instance_id: None,
+            digital_source_type: None,
+            reason: None,
+        }
+    }
+
+    fn to_json(&self) -> ActionJSON {
+        ActionJSON {
+            action: self.action.get_label(),
+            when: self
+                .when
+                .map(|when| when.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            software_agent: self.software_agent.as_deref(),
+            parameters: Some(&self.parameters).filter(|parameters| !parameters.is_empty()),
+            changes: Some(self.changes.iter().map(ActionRegion::to_json).collect())
+                .filter(|changes: &Vec<Value>| !changes.is_empty()),
+            instance_id: self.ingredient_instance_id.as_deref(),
+            digital_source_type: self
+                .digital_source_type
+                .as_ref()
+                .map(DigitalSourceType::get_uri),
+            reason: self.reason.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionJSON<'a> {
+    action: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    when: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    software_agent: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parameters: Option<&'a HashMap<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changes: Option<Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    digital_source_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ActionsJSON<'a> {
+    actions: Vec<ActionJSON<'a>>,
+}
+
+/// An action read from the `c2pa.actions` assertion of a signed manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionInfo {
+    /// Label of the action, such as `c2pa.cropped`.
+    pub action: String,
+    pub when: Option<String>,
+    /// Name of the software that performed the action.
+    pub software_agent: Option<String>,
+    pub digital_source_type: Option<String>,
+    /// Instance ID of the ingredient the action was performed on.
+    pub ingredient_instance_id: Option<String>,
+    pub reason: Option<String>,
+}
+
+fn get_string(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_owned)
+}
+
+impl ActionInfo {
+    fn from_json(action: &Value) -> Option<Self> {
+        // Version 2 of the assertion names the software agent in a ClaimGeneratorInfo.
+        let software_agent = action.get("softwareAgent").and_then(|agent| match agent {
+            Value::Object(_) => get_string(agent, "name"),
+            agent => agent.as_str().map(str::to_owned),
+        });
+        let ingredient_instance_id = get_string(action, "instanceId").or_else(|| {
+            action
+                .get("parameters")
+                .and_then(|parameters| get_string(parameters, "instanceId"))
+        });
+        Some(ActionInfo {
+            action: get_string(action, "action")?,
+            when: get_string(action, "when"),
+            software_agent,
+            digital_source_type: get_string(action, "digitalSourceType"),
+            ingredient_instance_id,
+            reason: get_string(action, "reason"),
+        })
+    }
+
+    /// Returns the actions of every `c2pa.actions` assertion, in the order they are listed.
+    pub(crate) fn from_assertions(
+        assertions: &[AssertionInfo],
+    ) -> Result<Vec<Self>, SimpleC2PAError> {
+        let mut actions = vec![];
+        for assertion in assertions {
+            if assertion.label != labels::ACTIONS && assertion.label != ACTIONS_V2_LABEL {
+                continue;
+            }
+            let value: Value = serde_json::from_str(&assertion.json)?;
+            let entries = value.get("actions").and_then(Value::as_array);
+            actions.extend(entries.into_iter().flatten().filter_map(Self::from_json));
+        }
+        Ok(actions)
+    }
+}
+
+/// Adds the actions recorded so far to `manifest` as a single `c2pa.actions` assertion.
+pub(crate) fn add_actions_assertion(
+    manifest: &mut Manifest,
+    actions: &[ActionData],
+) -> Result<(), SimpleC2PAError> {
+    if actions.is_empty() {
+        return Ok(());
+    }
+
+    let actions = ActionsJSON {
+        actions: actions.iter().map(ActionData::to_json).collect(),
+    };
+    manifest.add_labeled_assertion(labels::ACTIONS, &actions)?;
+    Ok(())
+}
+
+impl ContentCredentials {
+    /// Records an action in the `c2pa.actions` assertion. Actions are kept in the order they
+    /// are added and written as one assertion when the manifest is signed.
+    pub fn add_action(&self, action: ActionData) -> Result<(), SimpleC2PAError> {
+        if let ActionType::Other { label } = &action.action {
+            if label.is_empty() {
+                return Err(SimpleC2PAError::InvalidArgument {
+                    message: "Action label must not be empty".to_owned(),
+                });
+            }
+        }
+        self.actions.lock().unwrap().push(action);
+        Ok(())
+    }
+}
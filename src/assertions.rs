@@ -1,28 +1,47 @@
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::result::Result;
 
-use c2pa::assertions::{c2pa_action, labels, Action, Actions, Exif, SchemaDotOrg};
+use c2pa::assertions::{labels, Exif, SchemaDotOrg};
+use c2pa::Manifest;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::actions::{ActionData, ActionType};
+use crate::certificates::get_email_addresses;
 use crate::common::SimpleC2PAError;
 use crate::content_credentials::ContentCredentials;
+use crate::exif_values::{
+    direction_to_xmp, AltitudeRef, DirectionRef, Latitude, Longitude, Rational, SpeedRef,
+};
+use crate::location_privacy::LocationPrivacy;
+use crate::manifest_reader::AssertionInfo;
 
+/// EXIF metadata of the asset, written as XMP in the `stds.exif` assertion. Values are
+/// checked when the assertion is added.
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct ExifData<'a> {
-    pub gps_version_id: Option<Cow<'a, str>>,
-    pub latitude: Option<Cow<'a, str>>,
-    pub longitude: Option<Cow<'a, str>>,
-    pub altitude_ref: Option<u8>,
-    pub altitude: Option<Cow<'a, str>>,
-    pub timestamp: Option<Cow<'a, str>>,
-    pub speed_ref: Option<Cow<'a, str>>,
-    pub speed: Option<Cow<'a, str>>,
-    pub direction_ref: Option<Cow<'a, str>>,
-    pub direction: Option<Cow<'a, str>>,
-    pub destination_bearing_ref: Option<Cow<'a, str>>,
-    pub destination_bearing: Option<Cow<'a, str>>,
-    pub positioning_error: Option<Cow<'a, str>>,
-    pub exposure_time: Option<Cow<'a, str>>,
-    pub f_number: Option<f64>,
+    /// Version of the GPS fields, such as `[2, 2, 0, 0]`.
+    pub gps_version_id: Option<[u8; 4]>,
+    pub latitude: Option<Latitude>,
+    pub longitude: Option<Longitude>,
+    pub altitude_ref: Option<AltitudeRef>,
+    /// Altitude in meters.
+    pub altitude: Option<Rational>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub speed_ref: Option<SpeedRef>,
+    pub speed: Option<Rational>,
+    pub direction_ref: Option<DirectionRef>,
+    /// Direction the camera was facing, in degrees from 0 up to 360.
+    pub direction: Option<Rational>,
+    pub destination_bearing_ref: Option<DirectionRef>,
+    /// Bearing to the destination, in degrees from 0 up to 360.
+    pub destination_bearing: Option<Rational>,
+    /// Horizontal positioning error in meters.
+    pub positioning_error: Option<Rational>,
+    /// Exposure time in seconds, such as `1/100`.
+    pub exposure_time: Option<Rational>,
+    pub f_number: Option<Rational>,
     pub color_space: Option<u8>,
     pub digital_zoom_ratio: Option<f64>,
     pub make: Option<Cow<'a, str>>,
@@ -32,19 +51,49 @@ pub struct ExifData<'a> {
     pub lens_specification: Option<Vec<f64>>,
 }
 
+const TRAINING_MINING_LABEL: &str = "c2pa.training-mining";
+const AI_TRAINING: &str = "c2pa.ai_training";
+const AI_GENERATIVE_TRAINING: &str = "c2pa.ai_generative_training";
+const AI_INFERENCE: &str = "c2pa.ai_inference";
+const DATA_MINING: &str = "c2pa.data_mining";
+/// Label of the inference assertion in the legacy format, which uses one assertion per entry.
+const LEGACY_INFERENCE: &str = "c2pa.inference";
+
+/// An entry in the legacy format. Reading also accepts the `constraint_info` spelling of the
+/// standard format.
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct AIDataMiningUsageJSON<'a> {
     r#use: Cow<'a, str>,
+    #[serde(alias = "constraint_info")]
     r#constraint_info: Option<Cow<'a, str>>,
 }
 
+#[derive(Serialize)]
+struct TrainingMiningEntryJSON<'a> {
+    r#use: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    constraint_info: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct TrainingMiningJSON<'a> {
+    entries: BTreeMap<&'static str, TrainingMiningEntryJSON<'a>>,
+}
+
+#[derive(Deserialize)]
+struct TrainingMiningEntriesJSON<'a> {
+    entries: HashMap<String, AIDataMiningUsageJSON<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AIDataMiningUsage<'a> {
     Allowed,
     NotAllowed,
     Constrained { constraint_info: Cow<'a, str> },
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CustomAITrainingOptions<'a> {
     pub ai_training: AIDataMiningUsage<'a>,
     pub ai_generative_training: AIDataMiningUsage<'a>,
@@ -69,197 +118,500 @@ impl AIDataMiningUsage<'_> {
             },
         }
     }
+
+    fn to_entry_json(&self) -> TrainingMiningEntryJSON {
+        match self {
+            AIDataMiningUsage::Allowed => TrainingMiningEntryJSON {
+                r#use: "allowed",
+                constraint_info: None,
+            },
+            AIDataMiningUsage::NotAllowed => TrainingMiningEntryJSON {
+                r#use: "notAllowed",
+                constraint_info: None,
+            },
+            AIDataMiningUsage::Constrained { constraint_info } => TrainingMiningEntryJSON {
+                r#use: "constrained",
+                constraint_info: Some(constraint_info),
+            },
+        }
+    }
+
+    fn into_owned(self) -> AIDataMiningUsage<'static> {
+        match self {
+            AIDataMiningUsage::Allowed => AIDataMiningUsage::Allowed,
+            AIDataMiningUsage::NotAllowed => AIDataMiningUsage::NotAllowed,
+            AIDataMiningUsage::Constrained { constraint_info } => AIDataMiningUsage::Constrained {
+                constraint_info: Cow::Owned(constraint_info.into_owned()),
+            },
+        }
+    }
+
+    fn from_json(
+        json: AIDataMiningUsageJSON,
+    ) -> Result<AIDataMiningUsage<'static>, SimpleC2PAError> {
+        match json.r#use.as_ref() {
+            "allowed" => Ok(AIDataMiningUsage::Allowed),
+            "notAllowed" => Ok(AIDataMiningUsage::NotAllowed),
+            "constrained" => Ok(AIDataMiningUsage::Constrained {
+                constraint_info: Cow::Owned(
+                    json.r#constraint_info
+                        .map(Cow::into_owned)
+                        .unwrap_or_default(),
+                ),
+            }),
+            other => Err(SimpleC2PAError::InvalidArgument {
+                message: format!("{} is not a training and data mining use", other),
+            }),
+        }
+    }
 }
 
-fn get_creative_work_assertion(
-    name: &str,
-    identifier: &str,
-    id: &str,
-) -> Result<SchemaDotOrg, SimpleC2PAError> {
-    let person = SchemaDotOrg::new("Person".to_string())
+impl CustomAITrainingOptions<'_> {
+    fn into_owned(self) -> CustomAITrainingOptions<'static> {
+        CustomAITrainingOptions {
+            ai_training: self.ai_training.into_owned(),
+            ai_generative_training: self.ai_generative_training.into_owned(),
+            data_mining: self.data_mining.into_owned(),
+            inference: self.inference.into_owned(),
+        }
+    }
+
+    /// Reads the options from a `c2pa.training-mining` assertion, or from the assertions of
+    /// the legacy format. Uses that are not declared are reported as allowed, since nothing
+    /// restricts them.
+    pub(crate) fn from_assertions(
+        assertions: &[AssertionInfo],
+    ) -> Result<Option<CustomAITrainingOptions<'static>>, SimpleC2PAError> {
+        let mut entries = HashMap::new();
+        if let Some(assertion) = assertions
+            .iter()
+            .find(|assertion| assertion.label == TRAINING_MINING_LABEL)
+        {
+            let training_mining: TrainingMiningEntriesJSON = serde_json::from_str(&assertion.json)?;
+            entries = training_mining.entries;
+        } else {
+            for assertion in assertions {
+                if [
+                    AI_TRAINING,
+                    AI_GENERATIVE_TRAINING,
+                    DATA_MINING,
+                    LEGACY_INFERENCE,
+                ]
+                .contains(&assertion.label.as_str())
+                {
+                    let label = match assertion.label.as_str() {
+                        LEGACY_INFERENCE => AI_INFERENCE.to_owned(),
+                        label => label.to_owned(),
+                    };
+                    entries.insert(label, serde_json::from_str(&assertion.json)?);
+                }
+            }
+            if entries.is_empty() {
+                return Ok(None);
+            }
+        }
+
+        let mut usage = |label: &str| match entries.remove(label) {
+            Some(json) => AIDataMiningUsage::from_json(json),
+            None => Ok(AIDataMiningUsage::Allowed),
+        };
+        Ok(Some(CustomAITrainingOptions {
+            ai_training: usage(AI_TRAINING)?,
+            ai_generative_training: usage(AI_GENERATIVE_TRAINING)?,
+            data_mining: usage(DATA_MINING)?,
+            inference: usage(AI_INFERENCE)?,
+        }))
+    }
+}
+
+/// Adds the training and data mining options to `manifest`, as a single `c2pa.training-mining`
+/// assertion or, when `legacy` is set, as one assertion per use.
+pub(crate) fn add_training_mining_assertion(
+    manifest: &mut Manifest,
+    options: Option<&CustomAITrainingOptions>,
+    legacy: bool,
+) -> Result<(), SimpleC2PAError> {
+    let Some(options) = options else {
+        return Ok(());
+    };
+
+    if legacy {
+        manifest.add_labeled_assertion(AI_TRAINING, &options.ai_training.to_json())?;
+        manifest.add_labeled_assertion(
+            AI_GENERATIVE_TRAINING,
+            &options.ai_generative_training.to_json(),
+        )?;
+        manifest.add_labeled_assertion(DATA_MINING, &options.data_mining.to_json())?;
+        manifest.add_labeled_assertion(LEGACY_INFERENCE, &options.inference.to_json())?;
+        return Ok(());
+    }
+
+    let training_mining = TrainingMiningJSON {
+        entries: BTreeMap::from([
+            (AI_TRAINING, options.ai_training.to_entry_json()),
+            (
+                AI_GENERATIVE_TRAINING,
+                options.ai_generative_training.to_entry_json(),
+            ),
+            (AI_INFERENCE, options.inference.to_entry_json()),
+            (DATA_MINING, options.data_mining.to_entry_json()),
+        ]),
+    };
+    manifest.add_labeled_assertion(TRAINING_MINING_LABEL, &training_mining)?;
+    Ok(())
+}
+
+/// An account or key through which the author of an asset can be recognized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorIdentity {
+    Email {
+        address: String,
+    },
+    Pgp {
+        fingerprint: String,
+    },
+    Instagram {
+        username: String,
+    },
+    /// A Mastodon or other fediverse handle, such as `@jane@example.social`.
+    Mastodon {
+        handle: String,
+    },
+    Twitter {
+        username: String,
+    },
+    GitHub {
+        username: String,
+    },
+    Orcid {
+        id: String,
+    },
+    Keybase {
+        username: String,
+    },
+    Website {
+        url: String,
+    },
+}
+
+impl AuthorIdentity {
+    /// Returns the identifier of the account and the URL it can be looked up at.
+    fn get_identifier_and_url(&self) -> Result<(String, String), SimpleC2PAError> {
+        let identifier_and_url = match self {
+            AuthorIdentity::Email { address } => {
+                if !address.contains('@') {
+                    return Err(SimpleC2PAError::InvalidArgument {
+                        message: format!("{} is not an email address", address),
+                    });
+                }
+                (address.clone(), format!("mailto:{}", address))
+            }
+            AuthorIdentity::Pgp { fingerprint } => {
+                let fingerprint: String = fingerprint.split_whitespace().collect();
+                let url = format!("https://keys.openpgp.org/search?q={}", fingerprint);
+                (fingerprint, url)
+            }
+            AuthorIdentity::Instagram { username } => (
+                username.clone(),
+                format!("https://instagram.com/{}", username),
+            ),
+            AuthorIdentity::Mastodon { handle } => {
+                match handle.trim_start_matches('@').split_once('@') {
+                    Some((user, server)) if !user.is_empty() && !server.is_empty() => (
+                        format!("@{}@{}", user, server),
+                        format!("https://{}/@{}", server, user),
+                    ),
+                    _ => {
+                        return Err(SimpleC2PAError::InvalidArgument {
+                            message: format!("{} is not a fediverse handle", handle),
+                        })
+                    }
+                }
+            }
+            AuthorIdentity::Twitter { username } => {
+                (username.clone(), format!("https://x.com/{}", username))
+            }
+            AuthorIdentity::GitHub { username } => {
+                (username.clone(), format!("https://github.com/{}", username))
+            }
+            AuthorIdentity::Orcid { id } => (id.clone(), format!("https://orcid.org/{}", id)),
+            AuthorIdentity::Keybase { username } => {
+                (username.clone(), format!("https://keybase.io/{}", username))
+            }
+            AuthorIdentity::Website { url } => (url.clone(), url.clone()),
+        };
+        Ok(identifier_and_url)
+    }
+}
+
+/// The authors and links collected for the `stds.schema-org.CreativeWork` assertion.
+#[derive(Debug, Default)]
+pub(crate) struct CreativeWork {
+    authors: Vec<SchemaDotOrg>,
+    urls: Vec<String>,
+}
+
+fn get_person(name: &str, identities: &[AuthorIdentity]) -> Result<SchemaDotOrg, SimpleC2PAError> {
+    let mut identifiers_and_urls = vec![];
+    for identity in identities {
+        identifiers_and_urls.push(identity.get_identifier_and_url()?);
+    }
+    let Some(((identifier, id), others)) = identifiers_and_urls.split_first() else {
+        return Err(SimpleC2PAError::InvalidArgument {
+            message: "An author needs at least one identity".to_owned(),
+        });
+    };
+
+    let mut person = SchemaDotOrg::new("Person".to_string())
         .set_default_context()
         .insert("name".to_string(), name)?
         .insert("identifier".to_string(), identifier)?
         .insert("@id".to_string(), id)?;
+    if !others.is_empty() {
+        let same_as: Vec<&String> = others.iter().map(|(_, url)| url).collect();
+        person = person.insert("sameAs".to_string(), same_as)?;
+    }
+    let email = identities.iter().find_map(|identity| match identity {
+        AuthorIdentity::Email { address } => Some(address),
+        _ => None,
+    });
+    if let Some(email) = email {
+        person = person.insert("email".to_string(), email)?;
+    }
+    Ok(person)
+}
 
-    let work = SchemaDotOrg::new("CreativeWork".to_string())
-        .set_default_context()
-        .insert("author".to_string(), vec![person])?;
-    Ok(work)
+fn get_creative_work_assertion(
+    work: &CreativeWork,
+) -> Result<Option<SchemaDotOrg>, SimpleC2PAError> {
+    if work.authors.is_empty() && work.urls.is_empty() {
+        return Ok(None);
+    }
+
+    let mut assertion = SchemaDotOrg::new("CreativeWork".to_string()).set_default_context();
+    if !work.authors.is_empty() {
+        assertion = assertion.insert("author".to_string(), &work.authors)?;
+    }
+    if let Some((url, same_as)) = work.urls.split_first() {
+        assertion = assertion.insert("url".to_string(), url)?;
+        if !same_as.is_empty() {
+            assertion = assertion.insert("sameAs".to_string(), same_as)?;
+        }
+    }
+    Ok(Some(assertion))
 }
 
-fn get_actions_assertion(action: String) -> Result<Actions, SimpleC2PAError> {
-    let action = Action::new(action.as_str());
-    let actions = Actions::new().add_action(action);
-    Ok(actions)
+/// Adds the authors and links collected so far to `manifest` as a single
+/// `stds.schema-org.CreativeWork` assertion.
+pub(crate) fn add_creative_work_assertion(
+    manifest: &mut Manifest,
+    work: &CreativeWork,
+) -> Result<(), SimpleC2PAError> {
+    if let Some(assertion) = get_creative_work_assertion(work)? {
+        manifest.add_labeled_assertion(labels::CREATIVE_WORK, &assertion)?;
+    }
+    Ok(())
+}
+
+/// Builds the `stds.exif` assertion, checking that the values are in range.
+pub(crate) fn get_exif_assertion(exif_data: ExifData) -> Result<Exif, SimpleC2PAError> {
+    let mut exif = Exif::new();
+    if let Some(gps_version_id) = exif_data.gps_version_id {
+        let gps_version_id: Vec<String> = gps_version_id.iter().map(u8::to_string).collect();
+        exif = exif.insert("exif:GPSVersionID", gps_version_id.join("."))?;
+    }
+    if let Some(latitude) = exif_data.latitude {
+        exif = exif.insert("exif:GPSLatitude", latitude.to_xmp()?)?;
+    }
+    if let Some(longitude) = exif_data.longitude {
+        exif = exif.insert("exif:GPSLongitude", longitude.to_xmp()?)?;
+    }
+    if let Some(altitude_ref) = exif_data.altitude_ref {
+        exif = exif.insert("exif:GPSAltitudeRef", altitude_ref.get_value())?;
+    }
+    if let Some(altitude) = exif_data.altitude {
+        exif = exif.insert("exif:GPSAltitude", altitude.to_xmp("exif:GPSAltitude")?)?;
+    }
+    if let Some(timestamp) = exif_data.timestamp {
+        exif = exif.insert(
+            "exif:GPSTimeStamp",
+            timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        )?;
+    }
+    if let Some(speed_ref) = exif_data.speed_ref {
+        exif = exif.insert("exif:GPSSpeedRef", speed_ref.get_ref())?;
+    }
+    if let Some(speed) = exif_data.speed {
+        exif = exif.insert("exif:GPSSpeed", speed.to_xmp("exif:GPSSpeed")?)?;
+    }
+    if let Some(direction_ref) = exif_data.direction_ref {
+        exif = exif.insert("exif:GPSImgDirectionRef", direction_ref.get_ref())?;
+    }
+    if let Some(direction) = exif_data.direction {
+        exif = exif.insert(
+            "exif:GPSImgDirection",
+            direction_to_xmp("exif:GPSImgDirection", direction)?,
+        )?;
+    }
+    if let Some(destination_bearing_ref) = exif_data.destination_bearing_ref {
+        exif = exif.insert("exif:GPSDestBearingRef", destination_bearing_ref.get_ref())?;
+    }
+    if let Some(destination_bearing) = exif_data.destination_bearing {
+        exif = exif.insert(
+            "exif:GPSDestBearing",
+            direction_to_xmp("exif:GPSDestBearing", destination_bearing)?,
+        )?;
+    }
+    if let Some(positioning_error) = exif_data.positioning_error {
+        exif = exif.insert(
+            "exif:GPSHPositioningError",
+            positioning_error.to_xmp("exif:GPSHPositioningError")?,
+        )?;
+    }
+    if let Some(exposure_time) = exif_data.exposure_time {
+        exif = exif.insert(
+            "exif:ExposureTime",
+            exposure_time.to_xmp("exif:ExposureTime")?,
+        )?;
+    }
+    if let Some(f_number) = exif_data.f_number {
+        exif = exif.insert("exif:FNumber", f_number.to_xmp("exif:FNumber")?)?;
+    }
+    if let Some(color_space) = exif_data.color_space {
+        exif = exif.insert("exif:ColorSpace", color_space)?;
+    }
+    if let Some(digital_zoom_ratio) = exif_data.digital_zoom_ratio {
+        exif = exif.insert("exif:DigitalZoomRatio", digital_zoom_ratio)?;
+    }
+    if let Some(make) = exif_data.make {
+        exif = exif.insert("tiff:Make", make)?;
+    }
+    if let Some(model) = exif_data.model {
+        exif = exif.insert("tiff:Model", model)?;
+    }
+    if let Some(lens_make) = exif_data.lens_make {
+        exif = exif.insert("exifEX:LensMake", lens_make)?;
+    }
+    if let Some(lens_model) = exif_data.lens_model {
+        exif = exif.insert("exifEX:LensModel", lens_model)?;
+    }
+    if let Some(lens_specification) = exif_data.lens_specification {
+        exif = exif.insert("exifEX:LensSpecification", lens_specification)?;
+    }
+    Ok(exif)
 }
 
 impl ContentCredentials {
     pub fn add_created_assertion(&self) -> Result<(), SimpleC2PAError> {
-        let actions = get_actions_assertion(c2pa_action::CREATED.to_string())?;
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_assertion(&actions)?;
-        Ok(())
+        self.add_action(ActionData::new(ActionType::Created))
     }
 
     pub fn add_placed_assertion(&self) -> Result<(), SimpleC2PAError> {
-        let actions = get_actions_assertion(c2pa_action::PLACED.to_string())?;
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_assertion(&actions)?;
-        Ok(())
+        self.add_action(ActionData::new(ActionType::Placed))
     }
 
+    /// Adds the author's email address. When `check_certificate` is set the address must
+    /// also appear in the signing certificate, so that the assertion is backed by the signature.
     pub fn add_email_assertion(
         &self,
-        _email: String,
-        _display_name: String,
+        email: String,
+        display_name: String,
+        check_certificate: bool,
     ) -> Result<(), SimpleC2PAError> {
+        if check_certificate {
+            let certificate = self.get_signing_certificate_bytes()?;
+            let email_addresses = get_email_addresses(&certificate)?;
+            if !email_addresses
+                .iter()
+                .any(|email_address| email_address.eq_ignore_ascii_case(&email))
+            {
+                return Err(SimpleC2PAError::EmailMismatch { email });
+            }
+        }
+
+        self.add_author(display_name, vec![AuthorIdentity::Email { address: email }])
+    }
+
+    /// Adds an author, known by each of `identities`, to the CreativeWork assertion. The first
+    /// identity becomes the author's `@id` and the others are listed as `sameAs` links.
+    pub fn add_author(
+        &self,
+        display_name: String,
+        identities: Vec<AuthorIdentity>,
+    ) -> Result<(), SimpleC2PAError> {
+        let person = get_person(&display_name, &identities)?;
+        self.creative_work.lock().unwrap().authors.push(person);
         Ok(())
     }
 
     pub fn add_instagram_assertion(
         &self,
-        username: &str,
-        display_name: &str,
+        username: String,
+        display_name: String,
     ) -> Result<(), SimpleC2PAError> {
-        let work = get_creative_work_assertion(display_name, username, "https://instagram.com")?;
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion(labels::CREATIVE_WORK, &work)?;
-        Ok(())
+        self.add_author(display_name, vec![AuthorIdentity::Instagram { username }])
     }
 
     pub fn add_pgp_assertion(
         &self,
-        fingerprint: &str,
-        display_name: &str,
+        fingerprint: String,
+        display_name: String,
     ) -> Result<(), SimpleC2PAError> {
-        let work =
-            get_creative_work_assertion(display_name, fingerprint, "https://keys.openpgp.org")?;
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion(labels::CREATIVE_WORK, &work)?;
-        Ok(())
+        self.add_author(display_name, vec![AuthorIdentity::Pgp { fingerprint }])
     }
 
+    /// Links the CreativeWork to `url`. The first website added becomes its `url` and later
+    /// ones are listed as `sameAs` links.
     pub fn add_website_assertion(&self, url: String) -> Result<(), SimpleC2PAError> {
-        let work = SchemaDotOrg::new("CreativeWork".to_owned())
-            .set_default_context()
-            .insert("url".to_owned(), url)?;
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion(labels::CREATIVE_WORK, &work)?;
+        self.creative_work.lock().unwrap().urls.push(url);
         Ok(())
     }
 
     pub fn add_exif_assertion(&self, exif_data: ExifData) -> Result<(), SimpleC2PAError> {
-        let mut exif = Exif::new();
-        if let Some(gps_version_id) = exif_data.gps_version_id {
-            exif = exif.insert("exif:GPSVersionID", gps_version_id)?;
-        }
-        if let Some(latitude) = exif_data.latitude {
-            exif = exif.insert("exif:GPSLatitude", latitude)?;
-        }
-        if let Some(longitude) = exif_data.longitude {
-            exif = exif.insert("exif:GPSLongitude", longitude)?;
-        }
-        if let Some(altitude_ref) = exif_data.altitude_ref {
-            exif = exif.insert("exif:GPSAltitudeRef", altitude_ref)?;
-        }
-        if let Some(altitude) = exif_data.altitude {
-            exif = exif.insert("exif:GPSAltitude", altitude)?;
-        }
-        if let Some(timestamp) = exif_data.timestamp {
-            exif = exif.insert("exif:GPSTimeStamp", timestamp)?;
-        }
-        if let Some(speed_ref) = exif_data.speed_ref {
-            exif = exif.insert("exif:GPSSpeedRef", speed_ref)?;
-        }
-        if let Some(speed) = exif_data.speed {
-            exif = exif.insert("exif:GPSSpeed", speed)?;
-        }
-        if let Some(direction_ref) = exif_data.direction_ref {
-            exif = exif.insert("exif:GPSImgDirectionRef", direction_ref)?;
-        }
-        if let Some(direction) = exif_data.direction {
-            exif = exif.insert("exif:GPSImgDirection", direction)?;
-        }
-        if let Some(destination_bearing_ref) = exif_data.destination_bearing_ref {
-            exif = exif.insert("exif:GPSDestBearingRef", destination_bearing_ref)?;
-        }
-        if let Some(destination_bearing) = exif_data.destination_bearing {
-            exif = exif.insert("exif:GPSDestBearing", destination_bearing)?;
-        }
-        if let Some(positioning_error) = exif_data.positioning_error {
-            exif = exif.insert("exif:GPSHPositioningError", positioning_error)?;
-        }
-        if let Some(exposure_time) = exif_data.exposure_time {
-            exif = exif.insert("exif:ExposureTime", exposure_time)?;
-        }
-        if let Some(f_number) = exif_data.f_number {
-            exif = exif.insert("exif:FNumber", f_number)?;
-        }
-        if let Some(color_space) = exif_data.color_space {
-            exif = exif.insert("exif:ColorSpace", color_space)?;
-        }
-        if let Some(digital_zoom_ratio) = exif_data.digital_zoom_ratio {
-            exif = exif.insert("exif:DigitalZoomRatio", digital_zoom_ratio)?;
-        }
-        if let Some(make) = exif_data.make {
-            exif = exif.insert("tiff:Make", make)?;
-        }
-        if let Some(model) = exif_data.model {
-            exif = exif.insert("tiff:Model", model)?;
-        }
-        if let Some(lens_make) = exif_data.lens_make {
-            exif = exif.insert("exifEX:LensMake", lens_make)?;
-        }
-        if let Some(lens_model) = exif_data.lens_model {
-            exif = exif.insert("exifEX:LensModel", lens_model)?;
-        }
-        if let Some(lens_specification) = exif_data.lens_specification {
-            exif = exif.insert("exifEX:LensSpecification", lens_specification)?;
-        }
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_assertion(&exif)?;
+        self.add_exif_assertion_with_location_privacy(exif_data, LocationPrivacy::Exact)?;
         Ok(())
     }
 
     pub fn add_json_assertion(&self, label: &str, json: String) -> Result<(), SimpleC2PAError> {
+        let value: serde_json::Value = serde_json::from_str(&json)
+            .map_err(|source| SimpleC2PAError::InvalidAssertionJson { source })?;
         let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion(label, &json)?;
+        manifest.add_labeled_assertion(label, &value)?;
         Ok(())
     }
 
     pub fn add_restricted_ai_training_assertions(&self) -> Result<(), SimpleC2PAError> {
-        let training_not_allowed = AIDataMiningUsage::NotAllowed.to_json();
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion("c2pa.ai_training", &training_not_allowed)?;
-        manifest.add_labeled_assertion("c2pa.ai_generative_training", &training_not_allowed)?;
-        manifest.add_labeled_assertion("c2pa.data_mining", &training_not_allowed)?;
-        manifest.add_labeled_assertion("c2pa.inference", &training_not_allowed)?;
-        Ok(())
+        self.add_custom_ai_training_assertions(CustomAITrainingOptions {
+            ai_training: AIDataMiningUsage::NotAllowed,
+            ai_generative_training: AIDataMiningUsage::NotAllowed,
+            data_mining: AIDataMiningUsage::NotAllowed,
+            inference: AIDataMiningUsage::NotAllowed,
+        })
     }
 
     pub fn add_permissive_ai_training_assertions(&self) -> Result<(), SimpleC2PAError> {
-        let training_allowed = AIDataMiningUsage::Allowed.to_json();
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion("c2pa.ai_training", &training_allowed)?;
-        manifest.add_labeled_assertion("c2pa.ai_generative_training", &training_allowed)?;
-        manifest.add_labeled_assertion("c2pa.data_mining", &training_allowed)?;
-        manifest.add_labeled_assertion("c2pa.inference", &training_allowed)?;
-        Ok(())
+        self.add_custom_ai_training_assertions(CustomAITrainingOptions {
+            ai_training: AIDataMiningUsage::Allowed,
+            ai_generative_training: AIDataMiningUsage::Allowed,
+            data_mining: AIDataMiningUsage::Allowed,
+            inference: AIDataMiningUsage::Allowed,
+        })
     }
 
+    /// Sets the training and data mining options, written as a `c2pa.training-mining`
+    /// assertion when the manifest is signed. Replaces any options set before.
     pub fn add_custom_ai_training_assertions(
         &self,
         options: CustomAITrainingOptions,
     ) -> Result<(), SimpleC2PAError> {
-        let mut manifest = self.manifest.lock().unwrap();
-        manifest.add_labeled_assertion("c2pa.ai_training", &options.ai_training.to_json())?;
-        manifest.add_labeled_assertion(
-            "c2pa.ai_generative_training",
-            &options.ai_generative_training.to_json(),
-        )?;
-        manifest.add_labeled_assertion("c2pa.data_mining", &options.data_mining.to_json())?;
-        manifest.add_labeled_assertion("c2pa.inference", &options.inference.to_json())?;
+        *self.ai_training.lock().unwrap() = Some(options.into_owned());
         Ok(())
     }
+
+    /// Writes the training and data mining options as the four separate `c2pa.ai_training`,
+    /// `c2pa.ai_generative_training`, `c2pa.data_mining` and `c2pa.inference` assertions of
+    /// earlier versions, for readers that do not understand `c2pa.training-mining`.
+    pub fn set_legacy_ai_training_assertions(&self, legacy: bool) {
+        *self.legacy_ai_training.lock().unwrap() = legacy;
+    }
 }
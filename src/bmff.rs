@@ -0,0 +1,326 @@
+//! Signing of fragmented MP4 (fMP4), such as DASH and HLS recordings, with a BMFF hash over
+//! the Merkle tree of its fragments.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::sync::{Arc, Mutex};
+
+use c2pa::ManifestStore;
+use tempfile::{NamedTempFile, TempDir};
+use tracing::debug;
+
+use crate::common::{FileData, SimpleC2PAError};
+use crate::content_credentials::ContentCredentials;
+use crate::jumbf::{read_boxes, TYPE_SUPERBOX};
+use crate::manifest_reader::ManifestReader;
+
+/// Formats c2pa embeds manifests in with a BMFF hash.
+const BMFF_MIME_TYPES: [&str; 3] = ["video/mp4", "audio/mp4", "video/quicktime"];
+/// Extended type of the `uuid` box holding the manifest store.
+const C2PA_UUID: [u8; 16] = [
+    0xd8, 0xfe, 0xc3, 0xd6, 0x1b, 0x0e, 0x48, 0x3c, 0x92, 0x97, 0x58, 0x28, 0x87, 0x7e, 0xc4, 0x81,
+];
+const MANIFEST_PURPOSE: &[u8] = b"manifest";
+/// Size of the offset of the first Merkle map, between the purpose and the manifest store.
+const MERKLE_MAP_OFFSET_SIZE: usize = 8;
+const SIGNING_DIR_PREFIX: &str = ".c2pa-signing";
+const DEFAULT_INIT_SEGMENT_NAME: &str = "init.mp4";
+/// Prefix of the directories a fragmented recording is signed into.
+const SIGNED_DIR_PREFIX: &str = "signed-";
+/// Name of the file holding the name of the directory of the latest signed recording.
+const CURRENT_SIGNED_DIR_FILE_NAME: &str = "current";
+
+/// Returns the manifest store from the C2PA `uuid` box of a signed init segment.
+fn read_manifest_store(init_segment: &[u8]) -> Result<Vec<u8>, SimpleC2PAError> {
+    for bmff_box in read_boxes(init_segment)? {
+        if bmff_box.box_type != b"uuid" {
+            continue;
+        }
+        // The version and flags and the purpose precede the manifest store.
+        let Some(payload) = bmff_box
+            .payload
+            .strip_prefix(&C2PA_UUID)
+            .and_then(|payload| payload.get(4..))
+        else {
+            continue;
+        };
+        let Some(end) = payload.iter().position(|byte| *byte == 0) else {
+            return Err(SimpleC2PAError::InvalidInitSegment);
+        };
+        if &payload[..end] != MANIFEST_PURPOSE {
+            continue;
+        }
+        let manifest_store = payload[end + 1..]
+            .get(MERKLE_MAP_OFFSET_SIZE..)
+            .ok_or(SimpleC2PAError::InvalidInitSegment)?;
+        return match read_boxes(manifest_store)?.as_slice() {
+            [jumbf_box] if jumbf_box.box_type == TYPE_SUPERBOX => Ok(manifest_store.to_vec()),
+            _ => Err(SimpleC2PAError::InvalidInitSegment),
+        };
+    }
+    Err(SimpleC2PAError::InvalidInitSegment)
+}
+
+fn get_file_name(path: &Path) -> Result<PathBuf, SimpleC2PAError> {
+    path.file_name()
+        .map(PathBuf::from)
+        .ok_or_else(|| SimpleC2PAError::InvalidArgument {
+            message: format!("{} is not a file", path.display()),
+        })
+}
+
+/// Checks that signing will not replace the file at `path` with its signed copy.
+fn check_outside(path: &Path, output_dir: &Path) -> Result<(), SimpleC2PAError> {
+    let dir = fs::canonicalize(path)?.parent().map(Path::to_path_buf);
+    if dir == Some(fs::canonicalize(output_dir)?) {
+        return Err(SimpleC2PAError::InvalidArgument {
+            message: format!("{} is in the output directory", path.display()),
+        });
+    }
+    Ok(())
+}
+
+impl ContentCredentials {
+    /// Signs a fragmented MP4 whose init segment is the file of these credentials. The
+    /// manifest is embedded in the init segment and bound to `fragments` with a BMFF hash of
+    /// their Merkle tree, and each fragment gets the proof of its own hash, so that fragments
+    /// can be validated one at a time as they are streamed.
+    ///
+    /// The signed init segment and fragments are written under their original file names to
+    /// a new directory in `output_dir`, and returned in that order. Once every file has been
+    /// signed, the `current` file in `output_dir` is replaced in a single rename to name that
+    /// directory, and the directories signed before it are removed, so that
+    /// [`FragmentedRecording::get_signed_dir`] always returns a complete signed recording.
+    /// `output_dir` cannot be the directory the segments are read from.
+    pub fn embed_manifest_fragmented(
+        &self,
+        fragments: Vec<PathBuf>,
+        output_dir: PathBuf,
+    ) -> Result<Vec<Arc<FileData>>, SimpleC2PAError> {
+        let format = self.file.get_mime_type()?;
+        if !BMFF_MIME_TYPES.contains(&format.as_str()) {
+            return Err(SimpleC2PAError::UnsupportedFormat {
+                format,
+                supported: BMFF_MIME_TYPES.map(str::to_owned).to_vec(),
+            });
+        }
+        fs::create_dir_all(&output_dir)?;
+        let mut file_names = vec![];
+        for fragment in &fragments {
+            check_outside(fragment, &output_dir)?;
+            file_names.push(get_file_name(fragment)?);
+        }
+        let signing_dir = tempfile::Builder::new()
+            .prefix(SIGNING_DIR_PREFIX)
+            .tempdir_in(&output_dir)?;
+        let init_segment = match self.file.get_path() {
+            Some(path) => {
+                check_outside(&path, &output_dir)?;
+                path
+            }
+            None => {
+                let file_name = self
+                    .file
+                    .get_file_name()
+                    .unwrap_or_else(|| DEFAULT_INIT_SEGMENT_NAME.to_owned());
+                let path = signing_dir
+                    .path()
+                    .join(get_file_name(Path::new(&file_name))?);
+                fs::write(&path, self.file.get_bytes()?)?;
+                path
+            }
+        };
+        let init_segment_name = get_file_name(&init_segment)?;
+
+        let mut manifest = self.build_manifest()?;
+        let (signed_dir, _) = self.sign_manifest_with(&mut manifest, |manifest, signer| {
+            let alg = signer.alg();
+            let signed_dir = tempfile::Builder::new()
+                .prefix(SIGNED_DIR_PREFIX)
+                .tempdir_in(&output_dir)?;
+            manifest
+                .embed_to_bmff_fragmented(
+                    init_segment.as_path(),
+                    &fragments,
+                    signed_dir.path(),
+                    signer,
+                )
+                .map_err(|source| SimpleC2PAError::SigningFailed {
+                    algorithm: alg.to_string(),
+                    source,
+                })?;
+            let manifest_store =
+                read_manifest_store(&fs::read(signed_dir.path().join(&init_segment_name))?)?;
+            debug!(
+                "Signed manifest over {} fragments using {}",
+                fragments.len(),
+                alg
+            );
+            Ok((signed_dir, manifest_store))
+        })?;
+        let signed_dir = set_current_signed_dir(&output_dir, signed_dir)?;
+
+        Ok([&init_segment_name]
+            .into_iter()
+            .chain(&file_names)
+            .map(|file_name| FileData::new(Some(signed_dir.join(file_name)), None, None))
+            .collect())
+    }
+}
+
+/// Makes `signed_dir` the current signed recording in `output_dir`, and returns its path.
+fn set_current_signed_dir(
+    output_dir: &Path,
+    signed_dir: TempDir,
+) -> Result<PathBuf, SimpleC2PAError> {
+    let signed_dir = signed_dir.into_path();
+    let signed_dir_name = get_file_name(&signed_dir)?.to_string_lossy().into_owned();
+    let mut pointer = NamedTempFile::new_in(output_dir)?;
+    pointer.write_all(signed_dir_name.as_bytes())?;
+    pointer
+        .persist(output_dir.join(CURRENT_SIGNED_DIR_FILE_NAME))
+        .map_err(io::Error::from)?;
+    remove_signed_dirs(output_dir, &signed_dir_name)?;
+    Ok(signed_dir)
+}
+
+/// Removes the signed directories in `output_dir` other than `current`, left by earlier
+/// signing or by signing that was interrupted.
+fn remove_signed_dirs(output_dir: &Path, current: &str) -> Result<(), SimpleC2PAError> {
+    for entry in fs::read_dir(output_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(SIGNED_DIR_PREFIX) && name != current {
+            if let Err(error) = fs::remove_dir_all(entry.path()) {
+                debug!("Unable to remove {}: {}", name, error);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct RecordingState {
+    fragments: Vec<PathBuf>,
+    /// Number of the fragments covered by the latest signed recording.
+    signed_count: usize,
+}
+
+/// A fragmented MP4 recording that is signed while it is being captured, so that the
+/// fragments written so far stay covered by a manifest if capture stops unexpectedly.
+///
+/// The recording is signed with [`ContentCredentials::embed_manifest_fragmented`], so the
+/// directory returned by [`FragmentedRecording::get_signed_dir`] always holds a complete
+/// signed recording, even when the app is killed while signing.
+///
+/// The Merkle tree over the fragments changes as it grows, so the init segment and every
+/// fragment are rewritten each time the recording is signed: signing after each of n
+/// fragments costs O(n²) I/O over the capture. A signing interval bounds that cost, at the
+/// price of the fragments added since the last signature not being covered until the next.
+#[derive(Debug)]
+pub struct FragmentedRecording {
+    content_credentials: Arc<ContentCredentials>,
+    output_dir: PathBuf,
+    sign_interval: usize,
+    state: Mutex<RecordingState>,
+}
+
+impl FragmentedRecording {
+    /// Starts a recording whose init segment is the file of `content_credentials`, signed
+    /// again after every fragment. The signed recording is written to `output_dir`, which
+    /// must not be where the recorder writes its fragments.
+    pub fn new(content_credentials: Arc<ContentCredentials>, output_dir: PathBuf) -> Arc<Self> {
+        FragmentedRecording::new_with_interval(content_credentials, output_dir, 1)
+    }
+
+    /// Starts a recording like [`FragmentedRecording::new`] that is only signed again once
+    /// `sign_interval` fragments have been added since it was last signed. An interval of 0
+    /// is treated as 1.
+    pub fn new_with_interval(
+        content_credentials: Arc<ContentCredentials>,
+        output_dir: PathBuf,
+        sign_interval: u32,
+    ) -> Arc<Self> {
+        Arc::new(FragmentedRecording {
+            content_credentials,
+            output_dir,
+            sign_interval: sign_interval.max(1) as usize,
+            state: Mutex::new(RecordingState::default()),
+        })
+    }
+
+    /// Adds `fragment` once the recorder has finished writing it, and signs the recording
+    /// again into a new directory when the signing interval has been reached. Returns the
+    /// signed fragment, or `None` when the recording was not signed.
+    ///
+    /// When signing fails the fragment is not added, and the previously signed recording is
+    /// left as it was.
+    pub fn add_fragment(
+        &self,
+        fragment: PathBuf,
+    ) -> Result<Option<Arc<FileData>>, SimpleC2PAError> {
+        let mut state = self.state.lock().unwrap();
+        let mut fragments = state.fragments.clone();
+        fragments.push(fragment);
+        if fragments.len() - state.signed_count < self.sign_interval {
+            state.fragments = fragments;
+            return Ok(None);
+        }
+        let signed_files = self.sign_fragments(&mut state, fragments)?;
+        Ok(signed_files.last().cloned())
+    }
+
+    /// Signs the recording with every fragment added so far, such as when capture stops
+    /// before the signing interval has been reached. Returns the signed init segment and
+    /// fragments, in that order.
+    pub fn sign(&self) -> Result<Vec<Arc<FileData>>, SimpleC2PAError> {
+        let mut state = self.state.lock().unwrap();
+        let fragments = state.fragments.clone();
+        self.sign_fragments(&mut state, fragments)
+    }
+
+    fn sign_fragments(
+        &self,
+        state: &mut RecordingState,
+        fragments: Vec<PathBuf>,
+    ) -> Result<Vec<Arc<FileData>>, SimpleC2PAError> {
+        let signed_files = self
+            .content_credentials
+            .embed_manifest_fragmented(fragments.clone(), self.output_dir.clone())?;
+        state.signed_count = fragments.len();
+        state.fragments = fragments;
+        Ok(signed_files)
+    }
+
+    /// Returns the fragments added so far, in the order they were recorded.
+    pub fn get_fragments(&self) -> Vec<PathBuf> {
+        self.state.lock().unwrap().fragments.clone()
+    }
+
+    /// Returns the directory holding the latest signed recording in `output_dir`, or `None`
+    /// when nothing has been signed there yet. This also finds the recording after the app
+    /// was restarted.
+    pub fn get_signed_dir(output_dir: PathBuf) -> Result<Option<PathBuf>, SimpleC2PAError> {
+        match fs::read_to_string(output_dir.join(CURRENT_SIGNED_DIR_FILE_NAME)) {
+            Ok(name) => Ok(Some(output_dir.join(name))),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl ManifestReader {
+    /// Reads the manifest embedded in the init segment of a fragmented MP4 at `init_segment`,
+    /// validating `fragments` against it, each with the proof of its hash.
+    pub fn with_fragments(
+        init_segment: PathBuf,
+        fragments: Vec<PathBuf>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        let manifest_store =
+            ManifestStore::from_file_and_fragments(init_segment, &fragments, true)?;
+        Ok(Arc::new(ManifestReader { manifest_store }))
+    }
+}
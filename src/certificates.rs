@@ -1,16 +1,22 @@
+use std::fmt::Debug;
 use std::result::Result;
 use std::sync::Arc;
 
+use c2pa::SigningAlg;
 use openssl::asn1::Asn1Integer;
 use openssl::bn::BigNum;
 use openssl::ec::{EcGroup, EcKey};
 use openssl::hash::MessageDigest;
 use openssl::nid::Nid;
-use openssl::pkey::PKey;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::stack::Stack;
 use openssl::x509::extension::{
-    AuthorityKeyIdentifier, BasicConstraints, KeyUsage, SubjectKeyIdentifier,
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::{
+    X509Extension, X509Name, X509NameBuilder, X509ReqBuilder, X509VerifyResult, X509,
 };
-use openssl::x509::{X509Name, X509NameBuilder, X509};
 use ring::rand::SecureRandom;
 
 use crate::common::{FileData, SimpleC2PAError};
@@ -48,6 +54,10 @@ pub enum CertificateType<'a> {
         organization: Option<&'a str>,
         validity_days: Option<u32>,
     },
+    TimestampAuthority {
+        organization: Option<&'a str>,
+        validity_days: Option<u32>,
+    },
 }
 
 fn format_certificate_name(org: Option<&str>, name: &str) -> String {
@@ -62,6 +72,7 @@ impl CertificateType<'_> {
             CertificateType::OfflineRoot { .. } => true,
             CertificateType::OfflineIntermediate { .. } => true,
             CertificateType::ContentCredentials { .. } => false,
+            CertificateType::TimestampAuthority { .. } => false,
         }
     }
 
@@ -78,6 +89,9 @@ impl CertificateType<'_> {
             CertificateType::ContentCredentials { validity_days, .. } => {
                 validity_days.unwrap_or(365)
             }
+            CertificateType::TimestampAuthority { validity_days, .. } => {
+                validity_days.unwrap_or(365 * 10)
+            }
         }
     }
 
@@ -98,6 +112,9 @@ impl CertificateType<'_> {
             CertificateType::ContentCredentials { organization, .. } => {
                 organization.unwrap_or(DEFAULT_ORGANIZATION)
             }
+            CertificateType::TimestampAuthority { organization, .. } => {
+                organization.unwrap_or(DEFAULT_ORGANIZATION)
+            }
         }
     }
 
@@ -118,6 +135,9 @@ impl CertificateType<'_> {
             CertificateType::ContentCredentials { organization, .. } => {
                 format_certificate_name(*organization, "Content Credentials")
             }
+            CertificateType::TimestampAuthority { organization, .. } => {
+                format_certificate_name(*organization, "Timestamp Authority")
+            }
         }
     }
 }
@@ -154,7 +174,6 @@ impl CertificateOptions<'_> {
 pub struct Certificate {
     certificate_data: Arc<FileData>,
     private_key_data: Arc<FileData>,
-    #[allow(dead_code)]
     parent_certificate: Option<Arc<Certificate>>,
 }
 
@@ -178,8 +197,83 @@ impl Certificate {
     pub fn get_private_key_bytes(&self) -> Result<Vec<u8>, SimpleC2PAError> {
         self.private_key_data.get_bytes()
     }
+
+    /// Returns this certificate followed by the certificates that issued it, as concatenated
+    /// PEM. The self-signed root is only included when `include_root` is set.
+    pub fn get_certificate_chain_bytes(
+        &self,
+        include_root: bool,
+    ) -> Result<Vec<u8>, SimpleC2PAError> {
+        let mut chain = self.get_certificate_bytes()?;
+        let mut parent = self.parent_certificate.as_ref();
+        while let Some(certificate) = parent {
+            if !include_root && certificate.is_self_signed()? {
+                break;
+            }
+            if !chain.ends_with(b"\n") {
+                chain.push(b'\n');
+            }
+            chain.extend(certificate.get_certificate_bytes()?);
+            parent = certificate.parent_certificate.as_ref();
+        }
+        Ok(chain)
+    }
+
+    fn is_self_signed(&self) -> Result<bool, SimpleC2PAError> {
+        let certificate = X509::from_pem(&self.get_certificate_bytes()?)
+            .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
+        Ok(certificate.issued(&certificate) == X509VerifyResult::OK)
+    }
+
+    /// Returns the algorithm used to sign with this certificate's private key, checking that
+    /// the key belongs to the certificate. `preferred` must be compatible with the key type.
+    pub fn get_signing_algorithm(
+        &self,
+        preferred: Option<SigningAlg>,
+    ) -> Result<SigningAlg, SimpleC2PAError> {
+        let private_key = PKey::private_key_from_pem(&self.get_private_key_bytes()?)
+            .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
+        let certificate = X509::from_pem(&self.get_certificate_bytes()?)
+            .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
+        if !certificate.public_key()?.public_eq(&private_key) {
+            return Err(SimpleC2PAError::KeyMismatch);
+        }
+
+        let algorithms = signing_algorithms_for_key(&private_key)?;
+        match preferred {
+            None => Ok(algorithms[0]),
+            Some(algorithm) if algorithms.contains(&algorithm) => Ok(algorithm),
+            Some(algorithm) => Err(SimpleC2PAError::InvalidArgument {
+                message: format!("{} cannot be used with this private key", algorithm),
+            }),
+        }
+    }
 }
-fn generate_serial_number() -> Result<Asn1Integer, SimpleC2PAError> {
+
+/// Signing algorithms usable with the given key, the default one first.
+fn signing_algorithms_for_key(key: &PKey<Private>) -> Result<Vec<SigningAlg>, SimpleC2PAError> {
+    match key.id() {
+        Id::EC => match key.ec_key()?.group().curve_name() {
+            Some(Nid::X9_62_PRIME256V1) => Ok(vec![SigningAlg::Es256]),
+            Some(Nid::SECP384R1) => Ok(vec![SigningAlg::Es384]),
+            Some(Nid::SECP521R1) => Ok(vec![SigningAlg::Es512]),
+            curve => Err(SimpleC2PAError::UnsupportedKeyType {
+                key_type: format!("EC {:?}", curve),
+            }),
+        },
+        Id::RSA | Id::RSA_PSS => Ok(vec![
+            SigningAlg::Ps256,
+            SigningAlg::Ps384,
+            SigningAlg::Ps512,
+        ]),
+        Id::ED25519 => Ok(vec![SigningAlg::Ed25519]),
+        id => Err(SimpleC2PAError::UnsupportedKeyType {
+            key_type: format!("{:?}", id),
+        }),
+    }
+}
+
+pub(crate) fn generate_serial_number() -> Result<Asn1Integer, SimpleC2PAError> {
     let random = ring::rand::SystemRandom::new();
     let mut serial_number_bytes = [0u8; 20];
     let _ = random.fill(&mut serial_number_bytes);
@@ -203,11 +297,66 @@ fn create_name(options: &Arc<CertificateOptions>) -> Result<X509Name, SimpleC2PA
     Ok(name)
 }
 
+/// Returns the email addresses in the subject and subject alternative names of the first
+/// certificate in `certificate_pem`.
+pub(crate) fn get_email_addresses(certificate_pem: &[u8]) -> Result<Vec<String>, SimpleC2PAError> {
+    let certificate = X509::from_pem(certificate_pem)
+        .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
+    let mut email_addresses: Vec<String> = certificate
+        .subject_name()
+        .entries_by_nid(Nid::PKCS9_EMAILADDRESS)
+        .map(|entry| String::from_utf8_lossy(entry.data().as_slice()).into_owned())
+        .collect();
+    if let Some(names) = certificate.subject_alt_names() {
+        email_addresses.extend(
+            names
+                .iter()
+                .filter_map(|name| name.email().map(str::to_owned)),
+        );
+    }
+
+    Ok(email_addresses)
+}
+
+fn create_usage_extensions(
+    certificate_type: &CertificateType,
+) -> Result<Vec<X509Extension>, SimpleC2PAError> {
+    let is_ca = certificate_type.is_ca();
+    let mut basic_constraints = BasicConstraints::new();
+    if is_ca {
+        basic_constraints.critical().ca();
+    }
+
+    let mut key_usage = KeyUsage::new();
+    if is_ca {
+        key_usage.critical().key_cert_sign().crl_sign();
+    } else {
+        key_usage.digital_signature();
+    }
+
+    let mut extensions = vec![basic_constraints.build()?];
+    match certificate_type {
+        CertificateType::TimestampAuthority { .. } => {
+            key_usage.critical().non_repudiation();
+            extensions.push(key_usage.build()?);
+            extensions.push(ExtendedKeyUsage::new().critical().time_stamping().build()?);
+        }
+        _ if is_ca => extensions.push(key_usage.build()?),
+        _ => {
+            extensions.push(key_usage.build()?);
+            extensions.push(ExtendedKeyUsage::new().email_protection().build()?);
+        }
+    }
+
+    Ok(extensions)
+}
+
 pub fn create_certificate(
     options: Arc<CertificateOptions>,
 ) -> Result<Arc<Certificate>, SimpleC2PAError> {
     let serial_number = generate_serial_number()?;
-    let private_key = PKey::private_key_from_pem(&options.key.get_bytes()?)?;
+    let private_key = PKey::private_key_from_pem(&options.key.get_bytes()?)
+        .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
     let is_ca = options.certificate_type.is_ca();
     let name = create_name(&options)?;
 
@@ -215,7 +364,8 @@ pub fn create_certificate(
     cert_builder.set_version(2)?;
     cert_builder.set_subject_name(&name)?;
     if let Some(parent_certificate) = &options.parent_certificate {
-        let parent_cert = X509::from_pem(&parent_certificate.certificate_data.get_bytes()?)?;
+        let parent_cert = X509::from_pem(&parent_certificate.certificate_data.get_bytes()?)
+            .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
         cert_builder.set_issuer_name(parent_cert.subject_name())?;
     } else {
         cert_builder.set_issuer_name(&name)?;
@@ -229,19 +379,9 @@ pub fn create_certificate(
     cert_builder.set_not_before(&not_before)?;
     cert_builder.set_not_after(&not_after)?;
 
-    let mut basic_constraints = BasicConstraints::new();
-    if options.certificate_type.is_ca() {
-        basic_constraints.critical().ca();
-    }
-    cert_builder.append_extension(basic_constraints.build()?)?;
-
-    let mut key_usage = KeyUsage::new();
-    if is_ca {
-        key_usage.critical().key_cert_sign().crl_sign();
-    } else {
-        key_usage.digital_signature();
+    for extension in create_usage_extensions(&options.certificate_type)? {
+        cert_builder.append_extension(extension)?;
     }
-    cert_builder.append_extension(key_usage.build()?)?;
 
     let subject_key_id =
         SubjectKeyIdentifier::new().build(&cert_builder.x509v3_context(None, None))?;
@@ -254,17 +394,11 @@ pub fn create_certificate(
         cert_builder.append_extension(authority_key_id)?;
     }
 
-    if !is_ca {
-        let extended_key_usage = openssl::x509::extension::ExtendedKeyUsage::new()
-            .email_protection()
-            .build()?;
-        cert_builder.append_extension(extended_key_usage)?;
-    }
-
     let mut certificate_chain = vec![];
     if let Some(ref parent_certificate) = options.parent_certificate {
         let parent_private_key =
-            PKey::private_key_from_pem(&parent_certificate.private_key_data.get_bytes()?)?;
+            PKey::private_key_from_pem(&parent_certificate.private_key_data.get_bytes()?)
+                .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
         cert_builder.sign(&parent_private_key, MessageDigest::sha512())?;
         certificate_chain.push(parent_certificate);
     } else {
@@ -321,8 +455,97 @@ pub fn create_content_credentials_certificate(
     Ok(certificate)
 }
 
+/// Issues certificates for certificate signing requests, for example by submitting them to
+/// an organization's issuing CA.
+pub trait CertificateAuthority: Send + Sync + Debug {
+    /// Takes a PEM encoded PKCS#10 request and returns the issued certificate followed by the
+    /// certificates that issued it, as concatenated PEM.
+    fn sign_request(&self, request: String) -> Result<String, SimpleC2PAError>;
+}
+
+/// Creates a PEM encoded PKCS#10 certificate signing request for the key in `options`.
 pub fn request_signed_certificate(
-    _options: Arc<CertificateOptions>,
+    options: Arc<CertificateOptions>,
 ) -> Result<String, SimpleC2PAError> {
-    Ok("not yet implemented".to_string())
+    let private_key = PKey::private_key_from_pem(&options.key.get_bytes()?)
+        .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
+    let name = create_name(&options)?;
+
+    let mut request_builder = X509ReqBuilder::new()?;
+    request_builder.set_version(0)?;
+    request_builder.set_subject_name(&name)?;
+    request_builder.set_pubkey(&private_key)?;
+
+    let mut extensions = Stack::new()?;
+    for extension in create_usage_extensions(&options.certificate_type)? {
+        extensions.push(extension)?;
+    }
+    if let Some(email_address) = options.email_address {
+        let subject_alternative_name = SubjectAlternativeName::new()
+            .email(email_address)
+            .build(&request_builder.x509v3_context(None))?;
+        extensions.push(subject_alternative_name)?;
+    }
+    request_builder.add_extensions(&extensions)?;
+    request_builder.sign(&private_key, MessageDigest::sha512())?;
+
+    let request = request_builder.build();
+    Ok(String::from_utf8_lossy(&request.to_pem()?).into_owned())
+}
+
+/// Creates a [`Certificate`] from a CA-issued certificate and its issuing chain, given as
+/// concatenated PEM with the issued certificate first. The issuing certificates in the
+/// resulting chain carry no private key.
+pub fn import_signed_certificate(
+    key: Arc<FileData>,
+    certificate_chain: Arc<FileData>,
+) -> Result<Arc<Certificate>, SimpleC2PAError> {
+    let private_key = PKey::private_key_from_pem(&key.get_bytes()?)
+        .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
+    let certificates = X509::stack_from_pem(&certificate_chain.get_bytes()?)
+        .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
+
+    let Some((leaf, issuers)) = certificates.split_first() else {
+        return Err(SimpleC2PAError::InvalidArgument {
+            message: "Certificate chain is empty".to_owned(),
+        });
+    };
+    if !leaf.public_key()?.public_eq(&private_key) {
+        return Err(SimpleC2PAError::KeyMismatch);
+    }
+    for pair in certificates.windows(2) {
+        if pair[1].issued(&pair[0]) != X509VerifyResult::OK {
+            return Err(SimpleC2PAError::InvalidArgument {
+                message: "Certificate chain is not ordered from leaf to root".to_owned(),
+            });
+        }
+    }
+
+    let mut parent_certificate = None;
+    for issuer in issuers.iter().rev() {
+        parent_certificate = Some(Certificate::new(
+            FileData::new(None, Some(issuer.to_pem()?), None),
+            FileData::new(None, None, None),
+            parent_certificate,
+        ));
+    }
+
+    Ok(Certificate::new(
+        FileData::new(None, Some(leaf.to_pem()?), None),
+        key,
+        parent_certificate,
+    ))
+}
+
+/// Requests a certificate for the key in `options` from `authority` and imports the result.
+pub fn enroll_certificate(
+    options: Arc<CertificateOptions>,
+    authority: Arc<dyn CertificateAuthority>,
+) -> Result<Arc<Certificate>, SimpleC2PAError> {
+    let request = request_signed_certificate(options.clone())?;
+    let certificate_chain = authority.sign_request(request)?;
+    import_signed_certificate(
+        options.key.clone(),
+        FileData::new(None, Some(certificate_chain.into_bytes()), None),
+    )
 }
@@ -1,18 +1,20 @@
-use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
 use std::result::Result;
 use std::sync::Arc;
 
+use c2pa::CAIRead;
 use openssl::error::ErrorStack;
-use tempfile::{NamedTempFile, PersistError};
+use tempfile::PersistError;
 use thiserror::Error;
 
+use crate::media_format::{get_mime_type, get_supported_mime_types, HEADER_SIZE};
+
 #[derive(Debug, Clone)]
 pub struct FileData {
     path: Option<PathBuf>,
     bytes: Option<Vec<u8>>,
-    #[allow(dead_code)]
     file_name: Option<String>,
 }
 
@@ -38,65 +40,228 @@ impl FileData {
             return Ok(fs::read(path)?);
         }
 
-        Err(SimpleC2PAError::Failure {
-            message: "No bytes or path".to_owned(),
-        })
+        Err(SimpleC2PAError::MissingFileData)
     }
 
-    pub fn get_path(&self) -> Result<PathBuf, SimpleC2PAError> {
-        if let Some(ref path) = self.path {
-            return Ok(path.clone());
-        }
+    /// Returns the path backing this file, or `None` when it only exists in memory.
+    pub fn get_path(&self) -> Option<PathBuf> {
+        self.path.clone()
+    }
 
+    /// Opens the data for reading, preferring the in-memory bytes so that nothing is copied or
+    /// written to disk.
+    pub(crate) fn get_reader(&self) -> Result<Box<dyn CAIRead + '_>, SimpleC2PAError> {
         if let Some(bytes) = &self.bytes {
-            let mut file = NamedTempFile::new()?;
-            file.write_all(bytes)?;
-            return Ok(file.path().into());
+            return Ok(Box::new(Cursor::new(bytes.as_slice())));
         }
 
-        Err(SimpleC2PAError::Failure {
-            message: "No bytes or path".to_owned(),
+        if let Some(path) = &self.path {
+            return Ok(Box::new(File::open(path)?));
+        }
+
+        Err(SimpleC2PAError::MissingFileData)
+    }
+
+    /// Returns the file name given when this was created, else the last component of its path.
+    pub fn get_file_name(&self) -> Option<String> {
+        self.file_name.clone().or_else(|| {
+            self.path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|file_name| file_name.to_string_lossy().into_owned())
         })
     }
+
+    /// Returns the MIME type of the file, detected from its first bytes. The extension of its
+    /// name decides between formats that look the same, such as TIFF and DNG, and is used
+    /// alone when the content is not recognized.
+    pub fn get_mime_type(&self) -> Result<String, SimpleC2PAError> {
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        self.get_reader()?
+            .take(HEADER_SIZE as u64)
+            .read_to_end(&mut header)?;
+        let file_name = self.get_file_name();
+        let extension = file_name
+            .as_ref()
+            .and_then(|file_name| Path::new(file_name).extension())
+            .map(|extension| extension.to_string_lossy());
+
+        get_mime_type(&header, extension.as_deref())
+            .map(str::to_owned)
+            .ok_or_else(|| SimpleC2PAError::UnsupportedFormat {
+                format: file_name.unwrap_or_else(|| "unknown".to_owned()),
+                supported: get_supported_mime_types(),
+            })
+    }
 }
 
+/// Errors returned by this crate.
+///
+/// Every variant has a numeric code, returned by [`SimpleC2PAError::code`], that stays
+/// stable across releases so that callers on other platforms can branch on the kind of
+/// failure. New variants always get a new code.
 #[derive(Error, Debug)]
 pub enum SimpleC2PAError {
-    #[error("Failed with message: {message}")]
-    Failure { message: String },
+    #[error("I/O error: {source}")]
+    Io {
+        #[from]
+        source: std::io::Error,
+    },
 
-    #[error("unexpected id: {id}")]
-    Unexpected { id: i32 },
-}
+    #[error("File data has neither bytes nor a path")]
+    MissingFileData,
 
-impl From<std::io::Error> for SimpleC2PAError {
-    fn from(error: std::io::Error) -> Self {
-        SimpleC2PAError::Failure {
-            message: error.to_string(),
-        }
-    }
-}
+    #[error("Unable to persist temporary file: {source}")]
+    TempFile {
+        #[from]
+        source: PersistError,
+    },
 
-impl From<PersistError> for SimpleC2PAError {
-    fn from(error: PersistError) -> Self {
-        SimpleC2PAError::Failure {
-            message: error.to_string(),
-        }
-    }
+    #[error("Cryptography error: {source}")]
+    Crypto {
+        #[from]
+        source: ErrorStack,
+    },
+
+    #[error("Unable to parse certificate: {source}")]
+    CertificateParse { source: ErrorStack },
+
+    #[error("Unable to parse private key: {source}")]
+    PrivateKeyParse { source: ErrorStack },
+
+    #[error("Private key does not match the certificate")]
+    KeyMismatch,
+
+    #[error("Unsupported private key type: {key_type}")]
+    UnsupportedKeyType { key_type: String },
+
+    #[error("Email address {email} is not in the signing certificate")]
+    EmailMismatch { email: String },
+
+    #[error("Unsupported format: {format}, expected one of {}", supported.join(", "))]
+    UnsupportedFormat {
+        format: String,
+        supported: Vec<String>,
+    },
+
+    #[error("Signing with {algorithm} failed: {source}")]
+    SigningFailed {
+        algorithm: String,
+        source: c2pa::Error,
+    },
+
+    #[error("Timestamp request failed: {message}")]
+    Timestamp { message: String },
+
+    #[error(
+        "Identity assertion of {size} bytes does not fit the {reserved} bytes reserved for it"
+    )]
+    IdentityAssertionTooLarge { size: usize, reserved: usize },
+
+    #[error("No C2PA manifest found")]
+    ManifestNotFound,
+
+    #[error("Validation failed: {}", codes.join(", "))]
+    ValidationFailed { codes: Vec<String> },
+
+    #[error("Unable to fetch remote manifest from {url}: {message}")]
+    RemoteManifest { url: String, message: String },
+
+    #[error("No resource in the manifest store has URI {uri}")]
+    ResourceNotFound { uri: String },
+
+    #[error("Invalid JUMBF manifest store")]
+    InvalidJumbf,
+
+    #[error("The signed init segment has no manifest store")]
+    InvalidInitSegment,
+
+    #[error("Invalid assertion JSON: {source}")]
+    InvalidAssertionJson { source: serde_json::Error },
+
+    #[error("Serialization error: {source}")]
+    Serialization {
+        #[from]
+        source: serde_json::Error,
+    },
+
+    #[error("CBOR error: {source}")]
+    Cbor {
+        #[from]
+        source: serde_cbor::Error,
+    },
+
+    #[error("Unable to read EXIF data: {source}")]
+    Exif {
+        #[from]
+        source: exif::Error,
+    },
+
+    #[error("EXIF value of {field} is out of range: {value}")]
+    ExifValueOutOfRange { field: String, value: String },
+
+    #[error("Unable to redact {assertion}: {message}")]
+    InvalidRedaction { assertion: String, message: String },
+
+    #[error("Unable to create thumbnail: {source}")]
+    Thumbnail {
+        #[from]
+        source: image::ImageError,
+    },
+
+    #[error("Invalid argument: {message}")]
+    InvalidArgument { message: String },
+
+    #[error("C2PA error: {source}")]
+    C2PA { source: c2pa::Error },
 }
 
-impl From<ErrorStack> for SimpleC2PAError {
-    fn from(error: ErrorStack) -> Self {
-        SimpleC2PAError::Failure {
-            message: error.to_string(),
+impl SimpleC2PAError {
+    pub const fn code(&self) -> u32 {
+        match self {
+            SimpleC2PAError::Io { .. } => 100,
+            SimpleC2PAError::MissingFileData => 101,
+            SimpleC2PAError::TempFile { .. } => 102,
+            SimpleC2PAError::Crypto { .. } => 200,
+            SimpleC2PAError::CertificateParse { .. } => 201,
+            SimpleC2PAError::PrivateKeyParse { .. } => 202,
+            SimpleC2PAError::KeyMismatch => 203,
+            SimpleC2PAError::UnsupportedKeyType { .. } => 204,
+            SimpleC2PAError::EmailMismatch { .. } => 205,
+            SimpleC2PAError::UnsupportedFormat { .. } => 300,
+            SimpleC2PAError::SigningFailed { .. } => 400,
+            SimpleC2PAError::Timestamp { .. } => 401,
+            SimpleC2PAError::IdentityAssertionTooLarge { .. } => 402,
+            SimpleC2PAError::ManifestNotFound => 500,
+            SimpleC2PAError::ValidationFailed { .. } => 501,
+            SimpleC2PAError::RemoteManifest { .. } => 502,
+            SimpleC2PAError::ResourceNotFound { .. } => 503,
+            SimpleC2PAError::InvalidJumbf => 504,
+            SimpleC2PAError::InvalidInitSegment => 505,
+            SimpleC2PAError::InvalidAssertionJson { .. } => 600,
+            SimpleC2PAError::Serialization { .. } => 601,
+            SimpleC2PAError::Cbor { .. } => 602,
+            SimpleC2PAError::Exif { .. } => 603,
+            SimpleC2PAError::ExifValueOutOfRange { .. } => 604,
+            SimpleC2PAError::InvalidRedaction { .. } => 605,
+            SimpleC2PAError::Thumbnail { .. } => 606,
+            SimpleC2PAError::InvalidArgument { .. } => 700,
+            SimpleC2PAError::C2PA { .. } => 900,
         }
     }
 }
 
 impl From<c2pa::Error> for SimpleC2PAError {
     fn from(error: c2pa::Error) -> Self {
-        SimpleC2PAError::Failure {
-            message: error.to_string(),
+        match error {
+            c2pa::Error::JumbfNotFound | c2pa::Error::ProvenanceMissing => {
+                SimpleC2PAError::ManifestNotFound
+            }
+            c2pa::Error::UnsupportedType => SimpleC2PAError::UnsupportedFormat {
+                format: "unknown".to_owned(),
+                supported: get_supported_mime_types(),
+            },
+            source => SimpleC2PAError::C2PA { source },
         }
     }
 }
@@ -1,18 +1,31 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs;
+use std::io::Cursor;
 use std::path::PathBuf;
 use std::result::Result;
 use std::sync::{Arc, Mutex};
-use std::vec;
 
-use c2pa::{create_signer, Ingredient, Manifest, SigningAlg};
-use tracing::{debug, info};
-use tracing::error;
+use c2pa::{create_signer, Ingredient, Manifest, Relationship, Signer, SigningAlg};
+use tracing::debug;
 
+use crate::actions::{add_actions_assertion, ActionData};
+use crate::assertions::{
+    add_creative_work_assertion, add_training_mining_assertion, CreativeWork,
+    CustomAITrainingOptions,
+};
 use crate::certificates::Certificate;
 use crate::common::{FileData, SimpleC2PAError};
+use crate::identity::{IdentityAssertionBuilder, IdentityCredentialHolder};
+use crate::jumbf::read_assertion_references;
+use crate::remote_manifest::RemoteManifest;
+use crate::signer::{ClaimSigner, ClaimSignerAdapter};
+use crate::thumbnail::{add_thumbnails, ThumbnailSettings};
+use crate::timestamp::{TimestampAuthority, TimestampReservingSigner, TimestampingSigner};
 
 const APPLICATION_NAME: &str = "Simple-C2PA";
 const APPLICATION_VERSION: &str = env!("CARGO_PKG_VERSION");
+const SIDECAR_EXTENSION: &str = "c2pa";
 
 #[derive(Debug, Clone)]
 pub struct ApplicationInfo {
@@ -37,13 +50,29 @@ impl Display for ApplicationInfo {
     }
 }
 
+#[derive(Debug)]
+enum SigningCredential {
+    Certificate(Arc<Certificate>),
+    Signer(Arc<dyn ClaimSigner>),
+}
+
 #[derive(Debug)]
 pub struct ContentCredentials {
-    certificate: Arc<Certificate>,
-    file: Arc<FileData>,
+    credential: SigningCredential,
+    pub(crate) file: Arc<FileData>,
     #[allow(dead_code)]
     application_info: Arc<ApplicationInfo>,
     pub(crate) manifest: Mutex<Manifest>,
+    signing_algorithm: Mutex<Option<SigningAlg>>,
+    timestamp_authority: Mutex<Option<Arc<TimestampAuthority>>>,
+    pub(crate) actions: Mutex<Vec<ActionData>>,
+    pub(crate) creative_work: Mutex<CreativeWork>,
+    pub(crate) ai_training: Mutex<Option<CustomAITrainingOptions<'static>>>,
+    pub(crate) legacy_ai_training: Mutex<bool>,
+    pub(crate) thumbnails: Mutex<ThumbnailSettings>,
+    /// The files of the ingredients, by instance ID, to generate their thumbnails from.
+    ingredient_files: Mutex<HashMap<String, Arc<FileData>>>,
+    identity_credential_holder: Mutex<Option<Arc<dyn IdentityCredentialHolder>>>,
 }
 
 impl ContentCredentials {
@@ -51,103 +80,310 @@ impl ContentCredentials {
         certificate: Arc<Certificate>,
         file: Arc<FileData>,
         application_info: Option<Arc<ApplicationInfo>>,
-    ) -> Arc<Self> {
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        Self::with_credential(
+            SigningCredential::Certificate(certificate),
+            file,
+            application_info,
+        )
+    }
+
+    pub fn new_with_signer(
+        signer: Arc<dyn ClaimSigner>,
+        file: Arc<FileData>,
+        application_info: Option<Arc<ApplicationInfo>>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        Self::with_credential(SigningCredential::Signer(signer), file, application_info)
+    }
+
+    fn with_credential(
+        credential: SigningCredential,
+        file: Arc<FileData>,
+        application_info: Option<Arc<ApplicationInfo>>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
         let app_info = application_info.unwrap_or(ApplicationInfo::new(
             APPLICATION_NAME.to_owned(),
             APPLICATION_VERSION.to_owned(),
             None,
         ));
-        let path = file.get_path().unwrap();
-        let ingredient = Ingredient::from_file(path).unwrap();
-        // TODO: We shouldnt load it into bytes here
-        // ingredient
-        //     .set_thumbnail("image/jpeg", file.get_bytes().unwrap())
-        //     .unwrap();
+        let format = file.get_mime_type()?;
+        let ingredient = Ingredient::from_stream(&format, &mut file.get_reader()?)?;
+        let ingredient_files = HashMap::from([(ingredient.instance_id().to_owned(), file.clone())]);
         let claim_generator = app_info.to_string();
         let mut manifest = Manifest::new(claim_generator);
-        manifest.set_parent(ingredient).unwrap();
+        manifest.set_parent(ingredient)?;
 
-        Arc::new(ContentCredentials {
-            certificate,
+        Ok(Arc::new(ContentCredentials {
+            credential,
             file,
             application_info: app_info,
             manifest: Mutex::new(manifest),
-        })
+            signing_algorithm: Mutex::new(None),
+            timestamp_authority: Mutex::new(None),
+            actions: Mutex::new(vec![]),
+            creative_work: Mutex::new(CreativeWork::default()),
+            ai_training: Mutex::new(None),
+            legacy_ai_training: Mutex::new(false),
+            thumbnails: Mutex::new(ThumbnailSettings::default()),
+            ingredient_files: Mutex::new(ingredient_files),
+            identity_credential_holder: Mutex::new(None),
+        }))
     }
 
-    fn sign_manifest_with_certificate(
+    /// Records `file` as an ingredient of the signed asset, carrying forward any manifest it
+    /// already has. The signed asset is its own parent until a [`Relationship::ParentOf`]
+    /// ingredient is added, which replaces it; this is how an edit that produces a new file
+    /// records the original it was made from. Returns the instance ID of the ingredient, for
+    /// referencing it from an action.
+    pub fn add_ingredient(
         &self,
-        certificate: &Arc<Certificate>,
-        output_file: &Arc<FileData>,
-    ) -> Result<(), SimpleC2PAError> {
-        let cert = certificate.get_certificate_bytes()?;
-        let pkey = certificate.get_private_key_bytes()?;
-        let algorithms = vec![
-            SigningAlg::Es256,
-            SigningAlg::Es384,
-            SigningAlg::Es512,
-            SigningAlg::Ps256,
-            SigningAlg::Ps384,
-            SigningAlg::Ps512,
-            SigningAlg::Ed25519,
-        ];
-        for alg in algorithms {
-            info!("Trying algorithm {:?}... ", alg);
-            let signer = create_signer::from_keys(&cert, &pkey, alg, None);
-            info!("embedding manifest using signer");
-            match signer {
-                Ok(signer) => {
-                    let mut manifest = self.manifest.lock().unwrap();
-                    match manifest.embed(
-                        &self.file.get_path()?,
-                        &output_file.get_path()?,
-                        signer.as_ref(),
-                    ) {
-                        Ok(_) => {
-                            debug!("Using provided certificate and private key");
-                            break;
-                        }
-                        Err(err) => {
-                            error!("failed: {}\n", err);
-                            continue;
-                        }
+        file: Arc<FileData>,
+        relationship: Relationship,
+    ) -> Result<String, SimpleC2PAError> {
+        let format = file.get_mime_type()?;
+        let mut ingredient = Ingredient::from_stream(&format, &mut file.get_reader()?)?;
+        let instance_id = ingredient.instance_id().to_owned();
+        self.ingredient_files
+            .lock()
+            .unwrap()
+            .insert(instance_id.clone(), file);
+        let mut manifest = self.manifest.lock().unwrap();
+        match relationship {
+            Relationship::ParentOf => {
+                ingredient.set_is_parent();
+                match manifest
+                    .ingredients_mut()
+                    .iter_mut()
+                    .find(|ingredient| ingredient.is_parent())
+                {
+                    Some(parent) => *parent = ingredient,
+                    None => {
+                        manifest.set_parent(ingredient)?;
                     }
                 }
-                Err(err) => {
-                    error!("failed: {}\n", err);
-                    continue;
-                }
-            };
+            }
+            relationship => {
+                ingredient.set_relationship(relationship);
+                manifest.add_ingredient(ingredient);
+            }
         }
-        Ok(())
+        Ok(instance_id)
     }
 
-    fn sign_manifest(
+    /// Overrides the algorithm derived from the certificate's private key. Has no effect when
+    /// signing with a [`ClaimSigner`], which reports its own algorithm.
+    pub fn set_signing_algorithm(&self, algorithm: Option<SigningAlg>) {
+        *self.signing_algorithm.lock().unwrap() = algorithm;
+    }
+
+    /// Requests an RFC 3161 timestamp from `authority` for each signature, so that it remains
+    /// verifiable after the signing certificate expires.
+    pub fn set_timestamp_authority(&self, authority: Option<Arc<TimestampAuthority>>) {
+        *self.timestamp_authority.lock().unwrap() = authority;
+    }
+
+    /// Adds a CAWG identity assertion signed by `holder`, binding the identity of a named actor
+    /// to the manifest independently of the claim signing certificate.
+    pub fn set_identity_credential_holder(
         &self,
-        embed: bool,
-        output_path: Option<PathBuf>,
-    ) -> Result<Arc<FileData>, SimpleC2PAError> {
-        let output_file = FileData::new(output_path, None, None);
-        if !embed {
-            let mut manifest = self.manifest.lock().unwrap();
-            manifest.set_sidecar_manifest();
+        holder: Option<Arc<dyn IdentityCredentialHolder>>,
+    ) {
+        *self.identity_credential_holder.lock().unwrap() = holder;
+    }
+
+    fn create_signer(&self) -> Result<Box<dyn Signer>, SimpleC2PAError> {
+        let signer = self.create_credential_signer()?;
+        match self.timestamp_authority.lock().unwrap().clone() {
+            Some(authority) => Ok(Box::new(TimestampingSigner::new(signer, authority))),
+            None => Ok(signer),
+        }
+    }
+
+    /// Returns a signer for a signature that is only made to lay out the manifest store, such
+    /// as that of the placeholder identity assertion. It takes the space of a timestamp
+    /// without requesting one.
+    fn create_placeholder_signer(&self) -> Result<Box<dyn Signer>, SimpleC2PAError> {
+        let signer = self.create_credential_signer()?;
+        match *self.timestamp_authority.lock().unwrap() {
+            Some(_) => Ok(Box::new(TimestampReservingSigner::new(signer))),
+            None => Ok(signer),
+        }
+    }
+
+    fn create_credential_signer(&self) -> Result<Box<dyn Signer>, SimpleC2PAError> {
+        match &self.credential {
+            SigningCredential::Certificate(certificate) => {
+                let cert = certificate.get_certificate_chain_bytes(false)?;
+                let pkey = certificate.get_private_key_bytes()?;
+                let alg =
+                    certificate.get_signing_algorithm(*self.signing_algorithm.lock().unwrap())?;
+                create_signer::from_keys(&cert, &pkey, alg, None).map_err(|source| {
+                    SimpleC2PAError::SigningFailed {
+                        algorithm: alg.to_string(),
+                        source,
+                    }
+                })
+            }
+            SigningCredential::Signer(signer) => {
+                Ok(Box::new(ClaimSignerAdapter::new(signer.clone())?))
+            }
+        }
+    }
+
+    /// Returns the signing certificate, followed by the certificates that issued it apart from
+    /// the root, as concatenated PEM: the chain that goes into the signature.
+    pub(crate) fn get_signing_certificate_bytes(&self) -> Result<Vec<u8>, SimpleC2PAError> {
+        match &self.credential {
+            SigningCredential::Certificate(certificate) => {
+                certificate.get_certificate_chain_bytes(false)
+            }
+            SigningCredential::Signer(signer) => signer.get_certificate_chain(),
         }
+    }
+
+    /// Returns a copy of the manifest to sign, completed with the actions, authors and
+    /// thumbnails recorded so far.
+    pub(crate) fn build_manifest(&self) -> Result<Manifest, SimpleC2PAError> {
+        let mut manifest = self.manifest.lock().unwrap().clone();
+        add_actions_assertion(&mut manifest, &self.actions.lock().unwrap())?;
+        add_creative_work_assertion(&mut manifest, &self.creative_work.lock().unwrap())?;
+        add_training_mining_assertion(
+            &mut manifest,
+            self.ai_training.lock().unwrap().as_ref(),
+            *self.legacy_ai_training.lock().unwrap(),
+        )?;
+        add_thumbnails(
+            &mut manifest,
+            &self.file,
+            &self.thumbnails.lock().unwrap(),
+            &self.ingredient_files.lock().unwrap(),
+        )?;
+        Ok(manifest)
+    }
+
+    /// Signs `manifest` into a copy of the asset held in memory, returning the signed asset and
+    /// the manifest store.
+    fn sign_manifest(
+        &self,
+        manifest: &mut Manifest,
+    ) -> Result<(Vec<u8>, Vec<u8>), SimpleC2PAError> {
+        self.sign_manifest_with(manifest, |manifest, signer| {
+            self.embed_to_asset(manifest, signer)
+        })
+    }
+
+    /// Signs `manifest` with `embed`, which returns what it signed and the manifest store. With
+    /// an identity credential holder `embed` is called twice, first with a placeholder for the
+    /// identity assertion and a signer that requests no timestamp.
+    pub(crate) fn sign_manifest_with<T>(
+        &self,
+        manifest: &mut Manifest,
+        embed: impl Fn(&mut Manifest, &dyn Signer) -> Result<(T, Vec<u8>), SimpleC2PAError>,
+    ) -> Result<(T, Vec<u8>), SimpleC2PAError> {
+        let signer = self.create_signer()?;
+        let Some(holder) = self.identity_credential_holder.lock().unwrap().clone() else {
+            return embed(manifest, signer.as_ref());
+        };
+
+        let mut builder = IdentityAssertionBuilder::new(holder);
+        let mut placeholder_manifest = manifest.clone();
+        builder.add_placeholder(&mut placeholder_manifest)?;
+        let placeholder_signer = self.create_placeholder_signer()?;
+        let (_, manifest_store) = embed(&mut placeholder_manifest, placeholder_signer.as_ref())?;
+        builder.add_assertion(manifest, read_assertion_references(&manifest_store)?)?;
+        embed(manifest, signer.as_ref())
+    }
 
-        self.sign_manifest_with_certificate(&self.certificate, &output_file)?;
-        Ok(output_file)
+    fn embed_to_asset(
+        &self,
+        manifest: &mut Manifest,
+        signer: &dyn Signer,
+    ) -> Result<(Vec<u8>, Vec<u8>), SimpleC2PAError> {
+        let alg = signer.alg();
+        let format = self.file.get_mime_type()?;
+        let mut source = self.file.get_reader()?;
+        let mut output = Cursor::new(Vec::new());
+        let manifest_bytes = manifest
+            .embed_to_stream(&format, &mut source, &mut output, signer)
+            .map_err(|source| SimpleC2PAError::SigningFailed {
+                algorithm: alg.to_string(),
+                source,
+            })?;
+        debug!("Signed manifest using {}", alg);
+        Ok((output.into_inner(), manifest_bytes))
     }
 
+    /// Signs the asset with the manifest embedded in it. The signed asset is returned in memory
+    /// and, when `output_path` is given, also written to that path. MP4 and MOV files are bound
+    /// to the manifest with a BMFF hash; see [`ContentCredentials::embed_manifest_fragmented`]
+    /// for fragmented MP4.
     pub fn embed_manifest(
         &self,
         output_path: Option<PathBuf>,
     ) -> Result<Arc<FileData>, SimpleC2PAError> {
-        self.sign_manifest(true, output_path)
+        let mut manifest = self.build_manifest()?;
+        let (bytes, _) = self.sign_manifest(&mut manifest)?;
+        if let Some(path) = &output_path {
+            fs::write(path, &bytes)?;
+        }
+        Ok(FileData::new(
+            output_path,
+            Some(bytes),
+            self.file.get_file_name(),
+        ))
     }
 
+    /// Signs the manifest without modifying the asset and returns the manifest store, to be
+    /// published as a `.c2pa` sidecar next to the original. When `output_path` is given the
+    /// manifest store is also written there, with its extension replaced by `.c2pa`, so the
+    /// path of the asset itself may be passed.
     pub fn export_manifest(
         &self,
         output_path: Option<PathBuf>,
     ) -> Result<Arc<FileData>, SimpleC2PAError> {
-        self.sign_manifest(false, output_path)
+        let mut manifest = self.build_manifest()?;
+        manifest.set_sidecar_manifest();
+        let (_, manifest_bytes) = self.sign_manifest(&mut manifest)?;
+
+        let output_path = output_path.map(|path| path.with_extension(SIDECAR_EXTENSION));
+        if let Some(path) = &output_path {
+            fs::write(path, &manifest_bytes)?;
+        }
+        Ok(FileData::new(
+            output_path,
+            Some(manifest_bytes),
+            self.get_sidecar_file_name(),
+        ))
+    }
+
+    /// Signs the asset with only a reference to `url` embedded in it, as XMP
+    /// `dcterms:provenance`. Nothing is uploaded: the returned manifest store must be published
+    /// at `url` by the caller. When `output_path` is given the signed asset is also written there.
+    pub fn embed_remote_manifest(
+        &self,
+        url: String,
+        output_path: Option<PathBuf>,
+    ) -> Result<RemoteManifest, SimpleC2PAError> {
+        let mut manifest = self.build_manifest()?;
+        manifest.set_remote_manifest(url.clone());
+        let (bytes, manifest_bytes) = self.sign_manifest(&mut manifest)?;
+
+        if let Some(path) = &output_path {
+            fs::write(path, &bytes)?;
+        }
+        Ok(RemoteManifest {
+            url,
+            asset: FileData::new(output_path, Some(bytes), self.file.get_file_name()),
+            manifest_store: FileData::new(None, Some(manifest_bytes), self.get_sidecar_file_name()),
+        })
+    }
+
+    fn get_sidecar_file_name(&self) -> Option<String> {
+        self.file.get_file_name().map(|file_name| {
+            PathBuf::from(file_name)
+                .with_extension(SIDECAR_EXTENSION)
+                .to_string_lossy()
+                .into_owned()
+        })
     }
 }
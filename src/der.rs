@@ -0,0 +1,136 @@
+//! Just enough DER encoding and decoding to build and parse RFC 3161 messages.
+
+use std::result::Result;
+
+use crate::common::SimpleC2PAError;
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_NULL: u8 = 0x05;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+
+pub(crate) const fn context_specific(number: u8) -> u8 {
+    0xa0 | number
+}
+
+pub(crate) fn encode(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let length = content.len();
+    if length < 0x80 {
+        encoded.push(length as u8);
+    } else {
+        let length_bytes: Vec<u8> = length
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        encoded.push(0x80 | length_bytes.len() as u8);
+        encoded.extend(length_bytes);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+pub(crate) fn sequence(elements: &[&[u8]]) -> Vec<u8> {
+    encode(TAG_SEQUENCE, &elements.concat())
+}
+
+/// Encodes a SET OF, sorting the elements as DER requires.
+pub(crate) fn set_of(elements: &[&[u8]]) -> Vec<u8> {
+    let mut sorted = elements.to_vec();
+    sorted.sort();
+    encode(TAG_SET, &sorted.concat())
+}
+
+/// Encodes a non-negative integer from its big-endian magnitude.
+pub(crate) fn unsigned_integer(magnitude: &[u8]) -> Vec<u8> {
+    let trimmed: Vec<u8> = magnitude
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    let mut content = vec![];
+    if !matches!(trimmed.first(), Some(byte) if byte & 0x80 == 0) {
+        content.push(0);
+    }
+    content.extend(trimmed);
+    encode(TAG_INTEGER, &content)
+}
+
+pub(crate) fn oid(arcs: &[u64]) -> Vec<u8> {
+    let mut content = vec![(arcs[0] * 40 + arcs[1]) as u8];
+    for arc in &arcs[2..] {
+        let mut bytes = vec![(arc & 0x7f) as u8];
+        let mut remaining = arc >> 7;
+        while remaining > 0 {
+            bytes.push(0x80 | (remaining & 0x7f) as u8);
+            remaining >>= 7;
+        }
+        bytes.reverse();
+        content.extend(bytes);
+    }
+    encode(TAG_OID, &content)
+}
+
+pub(crate) fn algorithm_identifier(arcs: &[u64]) -> Vec<u8> {
+    sequence(&[&oid(arcs)])
+}
+
+/// A decoded TLV element that keeps its complete encoding alongside its content.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Element<'a> {
+    pub(crate) tag: u8,
+    pub(crate) content: &'a [u8],
+    pub(crate) encoded: &'a [u8],
+}
+
+fn invalid_der() -> SimpleC2PAError {
+    SimpleC2PAError::InvalidArgument {
+        message: "Invalid DER encoding".to_owned(),
+    }
+}
+
+/// Reads the first element of `input`, returning it with the remaining input.
+pub(crate) fn read_element(input: &[u8]) -> Result<(Element<'_>, &[u8]), SimpleC2PAError> {
+    let (&tag, rest) = input.split_first().ok_or_else(invalid_der)?;
+    let (&first_length_byte, rest) = rest.split_first().ok_or_else(invalid_der)?;
+    let (length, rest) = if first_length_byte < 0x80 {
+        (first_length_byte as usize, rest)
+    } else {
+        let count = (first_length_byte & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || rest.len() < count {
+            return Err(invalid_der());
+        }
+        let length = rest[..count]
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+        (length, &rest[count..])
+    };
+    if rest.len() < length {
+        return Err(invalid_der());
+    }
+
+    let header_length = input.len() - rest.len();
+    Ok((
+        Element {
+            tag,
+            content: &rest[..length],
+            encoded: &input[..header_length + length],
+        },
+        &rest[length..],
+    ))
+}
+
+/// Reads all elements contained in `input`.
+pub(crate) fn read_elements(mut input: &[u8]) -> Result<Vec<Element<'_>>, SimpleC2PAError> {
+    let mut elements = vec![];
+    while !input.is_empty() {
+        let (element, rest) = read_element(input)?;
+        elements.push(element);
+        input = rest;
+    }
+    Ok(elements)
+}
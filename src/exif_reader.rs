@@ -0,0 +1,372 @@
+use std::borrow::Cow;
+use std::io::BufReader;
+use std::result::Result;
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use exif::{Exif, Field, In, Tag, Value};
+use tracing::debug;
+
+use crate::assertions::{get_exif_assertion, ExifData};
+use crate::common::{FileData, SimpleC2PAError};
+use crate::content_credentials::ContentCredentials;
+use crate::exif_values::{
+    AltitudeRef, Angle, DirectionRef, Latitude, LatitudeRef, Longitude, LongitudeRef, Rational,
+    SpeedRef,
+};
+
+/// A field of [`ExifData`], used to choose which fields are asserted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExifField {
+    GpsVersionId,
+    Latitude,
+    Longitude,
+    AltitudeRef,
+    Altitude,
+    Timestamp,
+    SpeedRef,
+    Speed,
+    DirectionRef,
+    Direction,
+    DestinationBearingRef,
+    DestinationBearing,
+    PositioningError,
+    ExposureTime,
+    FNumber,
+    ColorSpace,
+    DigitalZoomRatio,
+    Make,
+    Model,
+    LensMake,
+    LensModel,
+    LensSpecification,
+}
+
+impl ExifField {
+    pub const ALL: [ExifField; 22] = [
+        ExifField::GpsVersionId,
+        ExifField::Latitude,
+        ExifField::Longitude,
+        ExifField::AltitudeRef,
+        ExifField::Altitude,
+        ExifField::Timestamp,
+        ExifField::SpeedRef,
+        ExifField::Speed,
+        ExifField::DirectionRef,
+        ExifField::Direction,
+        ExifField::DestinationBearingRef,
+        ExifField::DestinationBearing,
+        ExifField::PositioningError,
+        ExifField::ExposureTime,
+        ExifField::FNumber,
+        ExifField::ColorSpace,
+        ExifField::DigitalZoomRatio,
+        ExifField::Make,
+        ExifField::Model,
+        ExifField::LensMake,
+        ExifField::LensModel,
+        ExifField::LensSpecification,
+    ];
+
+    /// The fields that reveal where the asset was captured, written as `exif:GPS*` fields.
+    pub const GPS: [ExifField; 13] = [
+        ExifField::GpsVersionId,
+        ExifField::Latitude,
+        ExifField::Longitude,
+        ExifField::AltitudeRef,
+        ExifField::Altitude,
+        ExifField::Timestamp,
+        ExifField::SpeedRef,
+        ExifField::Speed,
+        ExifField::DirectionRef,
+        ExifField::Direction,
+        ExifField::DestinationBearingRef,
+        ExifField::DestinationBearing,
+        ExifField::PositioningError,
+    ];
+
+    /// Returns the name of the field in the EXIF assertion, such as `exif:GPSLatitude`.
+    pub const fn get_name(&self) -> &'static str {
+        match self {
+            ExifField::GpsVersionId => "exif:GPSVersionID",
+            ExifField::Latitude => "exif:GPSLatitude",
+            ExifField::Longitude => "exif:GPSLongitude",
+            ExifField::AltitudeRef => "exif:GPSAltitudeRef",
+            ExifField::Altitude => "exif:GPSAltitude",
+            ExifField::Timestamp => "exif:GPSTimeStamp",
+            ExifField::SpeedRef => "exif:GPSSpeedRef",
+            ExifField::Speed => "exif:GPSSpeed",
+            ExifField::DirectionRef => "exif:GPSImgDirectionRef",
+            ExifField::Direction => "exif:GPSImgDirection",
+            ExifField::DestinationBearingRef => "exif:GPSDestBearingRef",
+            ExifField::DestinationBearing => "exif:GPSDestBearing",
+            ExifField::PositioningError => "exif:GPSHPositioningError",
+            ExifField::ExposureTime => "exif:ExposureTime",
+            ExifField::FNumber => "exif:FNumber",
+            ExifField::ColorSpace => "exif:ColorSpace",
+            ExifField::DigitalZoomRatio => "exif:DigitalZoomRatio",
+            ExifField::Make => "tiff:Make",
+            ExifField::Model => "tiff:Model",
+            ExifField::LensMake => "exifEX:LensMake",
+            ExifField::LensModel => "exifEX:LensModel",
+            ExifField::LensSpecification => "exifEX:LensSpecification",
+        }
+    }
+}
+
+fn get_field(exif: &Exif, tag: Tag) -> Option<&Field> {
+    exif.get_field(tag, In::PRIMARY)
+}
+
+fn get_text(exif: &Exif, tag: Tag) -> Option<Cow<'static, str>> {
+    match &get_field(exif, tag)?.value {
+        Value::Ascii(values) => {
+            let text = String::from_utf8_lossy(values.first()?);
+            let text = text.trim_end_matches('\0').trim();
+            (!text.is_empty()).then(|| Cow::Owned(text.to_owned()))
+        }
+        _ => None,
+    }
+}
+
+fn get_rationals(exif: &Exif, tag: Tag) -> Option<&[exif::Rational]> {
+    match &get_field(exif, tag)?.value {
+        Value::Rational(values) if !values.is_empty() => Some(values),
+        _ => None,
+    }
+}
+
+fn get_rational(exif: &Exif, tag: Tag) -> Option<Rational> {
+    let value = get_rationals(exif, tag)?.first()?;
+    (value.denom != 0).then(|| Rational::new(value.num, value.denom))
+}
+
+fn get_number(exif: &Exif, tag: Tag) -> Option<f64> {
+    get_rational(exif, tag).map(|value| value.to_f64())
+}
+
+fn get_uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    get_field(exif, tag)?.value.get_uint(0)
+}
+
+/// Reads the degrees, minutes and seconds of a coordinate. Some cameras write fractional
+/// degrees or minutes, which are kept as decimal degrees.
+fn get_angle(exif: &Exif, tag: Tag) -> Option<Angle> {
+    let values = get_rationals(exif, tag)?;
+    if values.len() != 3 || values.iter().any(|value| value.denom == 0) {
+        return None;
+    }
+    if values[0].num % values[0].denom == 0 && values[1].num % values[1].denom == 0 {
+        return Some(Angle::DegreesMinutesSeconds {
+            degrees: values[0].num / values[0].denom,
+            minutes: values[1].num / values[1].denom,
+            seconds: values[2].to_f64(),
+        });
+    }
+    Some(Angle::Decimal {
+        degrees: values[0].to_f64() + values[1].to_f64() / 60.0 + values[2].to_f64() / 3600.0,
+    })
+}
+
+fn get_latitude(exif: &Exif) -> Option<Latitude> {
+    Some(Latitude {
+        angle: get_angle(exif, Tag::GPSLatitude)?,
+        reference: LatitudeRef::from_ref(&get_text(exif, Tag::GPSLatitudeRef)?)?,
+    })
+}
+
+fn get_longitude(exif: &Exif) -> Option<Longitude> {
+    Some(Longitude {
+        angle: get_angle(exif, Tag::GPSLongitude)?,
+        reference: LongitudeRef::from_ref(&get_text(exif, Tag::GPSLongitudeRef)?)?,
+    })
+}
+
+/// Combines the GPS date and time stamps, which are in UTC.
+fn get_gps_timestamp(exif: &Exif) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(&get_text(exif, Tag::GPSDateStamp)?, "%Y:%m:%d").ok()?;
+    let time = get_rationals(exif, Tag::GPSTimeStamp)?;
+    if time.len() != 3 || time.iter().any(|value| value.denom == 0) {
+        return None;
+    }
+    let seconds = time[2].to_f64();
+    let time = NaiveTime::from_hms_nano_opt(
+        time[0].num / time[0].denom,
+        time[1].num / time[1].denom,
+        seconds.trunc() as u32,
+        (seconds.fract() * 1e9) as u32,
+    )?;
+    Some(date.and_time(time).and_utc())
+}
+
+fn get_gps_version_id(exif: &Exif) -> Option<[u8; 4]> {
+    match &get_field(exif, Tag::GPSVersionID)?.value {
+        Value::Byte(values) => values.as_slice().try_into().ok(),
+        _ => None,
+    }
+}
+
+fn get_lens_specification(exif: &Exif) -> Option<Vec<f64>> {
+    let values = get_rationals(exif, Tag::LensSpecification)?;
+    // Unknown values are written as 0/0, which has no floating point equivalent.
+    if values.len() != 4 || values.iter().any(|value| value.denom == 0) {
+        return None;
+    }
+    Some(values.iter().map(exif::Rational::to_f64).collect())
+}
+
+impl ExifData<'static> {
+    /// Reads the EXIF data embedded in a JPEG, PNG, HEIF or TIFF file. Fields that are not
+    /// present, or cannot be written as XMP, are left empty, as are all fields of a file
+    /// without EXIF data.
+    pub fn from_file(file: &FileData) -> Result<Self, SimpleC2PAError> {
+        let mut reader = BufReader::new(file.get_reader()?);
+        let exif = match exif::Reader::new().read_from_container(&mut reader) {
+            Ok(exif) => exif,
+            Err(exif::Error::NotFound(_)) => return Ok(ExifData::default()),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(ExifData {
+            gps_version_id: get_gps_version_id(&exif),
+            latitude: get_latitude(&exif),
+            longitude: get_longitude(&exif),
+            altitude_ref: get_uint(&exif, Tag::GPSAltitudeRef).and_then(AltitudeRef::from_value),
+            altitude: get_rational(&exif, Tag::GPSAltitude),
+            timestamp: get_gps_timestamp(&exif),
+            speed_ref: get_text(&exif, Tag::GPSSpeedRef)
+                .and_then(|reference| SpeedRef::from_ref(&reference)),
+            speed: get_rational(&exif, Tag::GPSSpeed),
+            direction_ref: get_text(&exif, Tag::GPSImgDirectionRef)
+                .and_then(|reference| DirectionRef::from_ref(&reference)),
+            direction: get_rational(&exif, Tag::GPSImgDirection),
+            destination_bearing_ref: get_text(&exif, Tag::GPSDestBearingRef)
+                .and_then(|reference| DirectionRef::from_ref(&reference)),
+            destination_bearing: get_rational(&exif, Tag::GPSDestBearing),
+            positioning_error: get_rational(&exif, Tag::GPSHPositioningError),
+            exposure_time: get_rational(&exif, Tag::ExposureTime),
+            f_number: get_rational(&exif, Tag::FNumber),
+            color_space: get_uint(&exif, Tag::ColorSpace)
+                .and_then(|value| u8::try_from(value).ok()),
+            digital_zoom_ratio: get_number(&exif, Tag::DigitalZoomRatio),
+            make: get_text(&exif, Tag::Make),
+            model: get_text(&exif, Tag::Model),
+            lens_make: get_text(&exif, Tag::LensMake),
+            lens_model: get_text(&exif, Tag::LensModel),
+            lens_specification: get_lens_specification(&exif),
+        })
+    }
+}
+
+impl ExifData<'_> {
+    pub const fn has_field(&self, field: ExifField) -> bool {
+        match field {
+            ExifField::GpsVersionId => self.gps_version_id.is_some(),
+            ExifField::Latitude => self.latitude.is_some(),
+            ExifField::Longitude => self.longitude.is_some(),
+            ExifField::AltitudeRef => self.altitude_ref.is_some(),
+            ExifField::Altitude => self.altitude.is_some(),
+            ExifField::Timestamp => self.timestamp.is_some(),
+            ExifField::SpeedRef => self.speed_ref.is_some(),
+            ExifField::Speed => self.speed.is_some(),
+            ExifField::DirectionRef => self.direction_ref.is_some(),
+            ExifField::Direction => self.direction.is_some(),
+            ExifField::DestinationBearingRef => self.destination_bearing_ref.is_some(),
+            ExifField::DestinationBearing => self.destination_bearing.is_some(),
+            ExifField::PositioningError => self.positioning_error.is_some(),
+            ExifField::ExposureTime => self.exposure_time.is_some(),
+            ExifField::FNumber => self.f_number.is_some(),
+            ExifField::ColorSpace => self.color_space.is_some(),
+            ExifField::DigitalZoomRatio => self.digital_zoom_ratio.is_some(),
+            ExifField::Make => self.make.is_some(),
+            ExifField::Model => self.model.is_some(),
+            ExifField::LensMake => self.lens_make.is_some(),
+            ExifField::LensModel => self.lens_model.is_some(),
+            ExifField::LensSpecification => self.lens_specification.is_some(),
+        }
+    }
+
+    pub fn clear_field(&mut self, field: ExifField) {
+        match field {
+            ExifField::GpsVersionId => self.gps_version_id = None,
+            ExifField::Latitude => self.latitude = None,
+            ExifField::Longitude => self.longitude = None,
+            ExifField::AltitudeRef => self.altitude_ref = None,
+            ExifField::Altitude => self.altitude = None,
+            ExifField::Timestamp => self.timestamp = None,
+            ExifField::SpeedRef => self.speed_ref = None,
+            ExifField::Speed => self.speed = None,
+            ExifField::DirectionRef => self.direction_ref = None,
+            ExifField::Direction => self.direction = None,
+            ExifField::DestinationBearingRef => self.destination_bearing_ref = None,
+            ExifField::DestinationBearing => self.destination_bearing = None,
+            ExifField::PositioningError => self.positioning_error = None,
+            ExifField::ExposureTime => self.exposure_time = None,
+            ExifField::FNumber => self.f_number = None,
+            ExifField::ColorSpace => self.color_space = None,
+            ExifField::DigitalZoomRatio => self.digital_zoom_ratio = None,
+            ExifField::Make => self.make = None,
+            ExifField::Model => self.model = None,
+            ExifField::LensMake => self.lens_make = None,
+            ExifField::LensModel => self.lens_model = None,
+            ExifField::LensSpecification => self.lens_specification = None,
+        }
+    }
+
+    /// Returns the fields that are set.
+    pub fn get_fields(&self) -> Vec<ExifField> {
+        ExifField::ALL
+            .into_iter()
+            .filter(|field| self.has_field(*field))
+            .collect()
+    }
+
+    /// Clears every field that is not in `fields`.
+    pub fn retain_fields(&mut self, fields: &[ExifField]) {
+        for field in ExifField::ALL {
+            if !fields.contains(&field) {
+                self.clear_field(field);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &ExifData::default()
+    }
+
+    /// Clears every field whose value cannot be asserted, such as a latitude with 60 seconds.
+    fn clear_out_of_range_fields(&mut self) -> Result<(), SimpleC2PAError> {
+        for field in self.get_fields() {
+            let mut field_data = self.clone();
+            field_data.retain_fields(&[field]);
+            match get_exif_assertion(field_data) {
+                Ok(_) => {}
+                Err(error @ SimpleC2PAError::ExifValueOutOfRange { .. }) => {
+                    debug!("Skipping EXIF field {}: {}", field.get_name(), error);
+                    self.clear_field(field);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ContentCredentials {
+    /// Adds an EXIF assertion with the EXIF data of the file being signed, limited to `fields`
+    /// when given. Fields whose values are out of range are left out rather than failing the
+    /// assertion, and nothing is added when the file has none of the fields.
+    pub fn add_exif_assertion_from_source(
+        &self,
+        fields: Option<Vec<ExifField>>,
+    ) -> Result<(), SimpleC2PAError> {
+        let mut exif_data = ExifData::from_file(&self.file)?;
+        if let Some(fields) = fields {
+            exif_data.retain_fields(&fields);
+        }
+        exif_data.clear_out_of_range_fields()?;
+        if exif_data.is_empty() {
+            return Ok(());
+        }
+        self.add_exif_assertion(exif_data)
+    }
+}
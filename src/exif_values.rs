@@ -0,0 +1,342 @@
+use std::result::Result;
+
+use crate::common::SimpleC2PAError;
+
+const MAX_LATITUDE: f64 = 90.0;
+const MAX_LONGITUDE: f64 = 180.0;
+/// Precision of the minutes of an angle, the six decimals of [`format_decimal`].
+const MICRO_MINUTES_PER_MINUTE: f64 = 1e6;
+
+pub(crate) fn out_of_range(field: &str, value: impl ToString) -> SimpleC2PAError {
+    SimpleC2PAError::ExifValueOutOfRange {
+        field: field.to_owned(),
+        value: value.to_string(),
+    }
+}
+
+/// Formats a number without trailing zeros, such as `21.102`.
+pub(crate) fn format_decimal(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
+
+/// An unsigned rational number, the way EXIF stores most measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Rational {
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        Rational {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// Returns the rational in XMP syntax, such as `1/100`.
+    pub(crate) fn to_xmp(self, field: &str) -> Result<String, SimpleC2PAError> {
+        if self.denominator == 0 {
+            return Err(out_of_range(field, self));
+        }
+        Ok(self.to_string())
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+/// The magnitude of a latitude or longitude. Its direction is given by the reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Angle {
+    Decimal {
+        degrees: f64,
+    },
+    DegreesMinutesSeconds {
+        degrees: u32,
+        minutes: u32,
+        seconds: f64,
+    },
+}
+
+impl Angle {
+    pub fn to_degrees(&self) -> f64 {
+        match *self {
+            Angle::Decimal { degrees } => degrees,
+            Angle::DegreesMinutesSeconds {
+                degrees,
+                minutes,
+                seconds,
+            } => degrees as f64 + minutes as f64 / 60.0 + seconds / 3600.0,
+        }
+    }
+
+    fn validate(&self, field: &str, max_degrees: f64) -> Result<(), SimpleC2PAError> {
+        let in_range = match *self {
+            Angle::Decimal { degrees } => degrees.is_finite() && degrees >= 0.0,
+            Angle::DegreesMinutesSeconds {
+                minutes, seconds, ..
+            } => minutes < 60 && seconds.is_finite() && (0.0..60.0).contains(&seconds),
+        } && self.to_degrees() <= max_degrees;
+        if !in_range {
+            return Err(out_of_range(field, self.to_xmp()));
+        }
+        Ok(())
+    }
+
+    /// Returns the angle in XMP syntax, as `DDD,MM,SS` or, when the seconds are not whole,
+    /// `DDD,MM.mm`.
+    fn to_xmp(self) -> String {
+        match self {
+            Angle::DegreesMinutesSeconds {
+                degrees,
+                minutes,
+                seconds,
+            } if seconds.fract() == 0.0 => format!("{},{},{}", degrees, minutes, seconds),
+            _ => {
+                let degrees = self.to_degrees();
+                if !degrees.is_finite() || degrees < 0.0 {
+                    return format_decimal(degrees);
+                }
+                // Rounded as a whole, so that minutes rounded up to 60 carry into the degrees.
+                let micro_minutes = (degrees * 60.0 * MICRO_MINUTES_PER_MINUTE).round() as u64;
+                let micro_minutes_per_degree = 60 * MICRO_MINUTES_PER_MINUTE as u64;
+                format!(
+                    "{},{}",
+                    micro_minutes / micro_minutes_per_degree,
+                    format_decimal(
+                        (micro_minutes % micro_minutes_per_degree) as f64
+                            / MICRO_MINUTES_PER_MINUTE
+                    )
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatitudeRef {
+    North,
+    South,
+}
+
+impl LatitudeRef {
+    pub const fn get_ref(&self) -> &'static str {
+        match self {
+            LatitudeRef::North => "N",
+            LatitudeRef::South => "S",
+        }
+    }
+
+    pub(crate) fn from_ref(reference: &str) -> Option<Self> {
+        match reference {
+            "N" => Some(LatitudeRef::North),
+            "S" => Some(LatitudeRef::South),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongitudeRef {
+    East,
+    West,
+}
+
+impl LongitudeRef {
+    pub const fn get_ref(&self) -> &'static str {
+        match self {
+            LongitudeRef::East => "E",
+            LongitudeRef::West => "W",
+        }
+    }
+
+    pub(crate) fn from_ref(reference: &str) -> Option<Self> {
+        match reference {
+            "E" => Some(LongitudeRef::East),
+            "W" => Some(LongitudeRef::West),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Latitude {
+    pub angle: Angle,
+    pub reference: LatitudeRef,
+}
+
+impl Latitude {
+    /// Creates a latitude from signed decimal degrees, negative to the south.
+    pub fn from_decimal(degrees: f64) -> Result<Self, SimpleC2PAError> {
+        let reference = if degrees < 0.0 {
+            LatitudeRef::South
+        } else {
+            LatitudeRef::North
+        };
+        let latitude = Latitude {
+            angle: Angle::Decimal {
+                degrees: degrees.abs(),
+            },
+            reference,
+        };
+        latitude.angle.validate("exif:GPSLatitude", MAX_LATITUDE)?;
+        Ok(latitude)
+    }
+
+    /// Returns signed decimal degrees, negative to the south.
+    pub fn to_decimal(&self) -> f64 {
+        match self.reference {
+            LatitudeRef::North => self.angle.to_degrees(),
+            LatitudeRef::South => -self.angle.to_degrees(),
+        }
+    }
+
+    /// Returns the latitude in XMP syntax, such as `39,21.102N`.
+    pub(crate) fn to_xmp(self) -> Result<String, SimpleC2PAError> {
+        self.angle.validate("exif:GPSLatitude", MAX_LATITUDE)?;
+        Ok(format!(
+            "{}{}",
+            self.angle.to_xmp(),
+            self.reference.get_ref()
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Longitude {
+    pub angle: Angle,
+    pub reference: LongitudeRef,
+}
+
+impl Longitude {
+    /// Creates a longitude from signed decimal degrees, negative to the west.
+    pub fn from_decimal(degrees: f64) -> Result<Self, SimpleC2PAError> {
+        let reference = if degrees < 0.0 {
+            LongitudeRef::West
+        } else {
+            LongitudeRef::East
+        };
+        let longitude = Longitude {
+            angle: Angle::Decimal {
+                degrees: degrees.abs(),
+            },
+            reference,
+        };
+        longitude
+            .angle
+            .validate("exif:GPSLongitude", MAX_LONGITUDE)?;
+        Ok(longitude)
+    }
+
+    /// Returns signed decimal degrees, negative to the west.
+    pub fn to_decimal(&self) -> f64 {
+        match self.reference {
+            LongitudeRef::East => self.angle.to_degrees(),
+            LongitudeRef::West => -self.angle.to_degrees(),
+        }
+    }
+
+    /// Returns the longitude in XMP syntax, such as `74,26.5737W`.
+    pub(crate) fn to_xmp(self) -> Result<String, SimpleC2PAError> {
+        self.angle.validate("exif:GPSLongitude", MAX_LONGITUDE)?;
+        Ok(format!(
+            "{}{}",
+            self.angle.to_xmp(),
+            self.reference.get_ref()
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AltitudeRef {
+    AboveSeaLevel,
+    BelowSeaLevel,
+}
+
+impl AltitudeRef {
+    pub const fn get_value(&self) -> u8 {
+        match self {
+            AltitudeRef::AboveSeaLevel => 0,
+            AltitudeRef::BelowSeaLevel => 1,
+        }
+    }
+
+    pub(crate) const fn from_value(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(AltitudeRef::AboveSeaLevel),
+            1 => Some(AltitudeRef::BelowSeaLevel),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedRef {
+    KilometersPerHour,
+    MilesPerHour,
+    Knots,
+}
+
+impl SpeedRef {
+    pub const fn get_ref(&self) -> &'static str {
+        match self {
+            SpeedRef::KilometersPerHour => "K",
+            SpeedRef::MilesPerHour => "M",
+            SpeedRef::Knots => "N",
+        }
+    }
+
+    pub(crate) fn from_ref(reference: &str) -> Option<Self> {
+        match reference {
+            "K" => Some(SpeedRef::KilometersPerHour),
+            "M" => Some(SpeedRef::MilesPerHour),
+            "N" => Some(SpeedRef::Knots),
+            _ => None,
+        }
+    }
+}
+
+/// The north that a direction or bearing is measured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionRef {
+    TrueNorth,
+    MagneticNorth,
+}
+
+impl DirectionRef {
+    pub const fn get_ref(&self) -> &'static str {
+        match self {
+            DirectionRef::TrueNorth => "T",
+            DirectionRef::MagneticNorth => "M",
+        }
+    }
+
+    pub(crate) fn from_ref(reference: &str) -> Option<Self> {
+        match reference {
+            "T" => Some(DirectionRef::TrueNorth),
+            "M" => Some(DirectionRef::MagneticNorth),
+            _ => None,
+        }
+    }
+}
+
+/// Returns a direction in XMP syntax, checking that it is less than a full turn.
+pub(crate) fn direction_to_xmp(
+    field: &str,
+    direction: Rational,
+) -> Result<String, SimpleC2PAError> {
+    let xmp = direction.to_xmp(field)?;
+    if direction.to_f64() >= 360.0 {
+        return Err(out_of_range(field, xmp));
+    }
+    Ok(xmp)
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::result::Result;
+use std::sync::Arc;
+
+use c2pa::assertions::UserCbor;
+use c2pa::{Manifest, Signer, SigningAlg};
+use serde::{Deserialize, Serialize};
+use serde_cbor::Value;
+
+use crate::common::SimpleC2PAError;
+use crate::jumbf::AssertionReference;
+use crate::signer::{ClaimSigner, ClaimSignerAdapter};
+
+pub(crate) const IDENTITY_LABEL: &str = "cawg.identity";
+pub const X509_SIGNATURE_TYPE: &str = "cawg.x509.cose";
+
+const COSE_SIGN1_TAG: u64 = 18;
+const COSE_HEADER_ALG: i128 = 1;
+const COSE_HEADER_X5CHAIN: i128 = 33;
+/// Padding reserved beyond the signature size, for the referenced assertions.
+const REFERENCED_ASSERTIONS_RESERVE_SIZE: usize = 4096;
+
+/// Holds a credential that binds a named actor to the manifest, as described by the CAWG
+/// identity assertion specification.
+pub trait IdentityCredentialHolder: Send + Sync + Debug {
+    /// Returns the `sig_type` of the signatures, such as [`X509_SIGNATURE_TYPE`].
+    fn get_signature_type(&self) -> String;
+
+    /// Returns the largest size in bytes of a signature.
+    fn get_reserve_size(&self) -> u32;
+
+    /// Signs the CBOR encoded [`SignerPayload`].
+    fn sign(&self, signer_payload: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError>;
+}
+
+/// An X.509 identity credential that signs the signer payload as a COSE_Sign1 structure with
+/// the certificate chain in its `x5chain` header.
+#[derive(Debug)]
+pub struct X509CredentialHolder {
+    signer: ClaimSignerAdapter,
+}
+
+impl X509CredentialHolder {
+    pub fn new(signer: Arc<dyn ClaimSigner>) -> Result<Arc<Self>, SimpleC2PAError> {
+        Ok(Arc::new(X509CredentialHolder {
+            signer: ClaimSignerAdapter::new(signer)?,
+        }))
+    }
+}
+
+const fn cose_algorithm(algorithm: SigningAlg) -> i128 {
+    match algorithm {
+        SigningAlg::Es256 => -7,
+        SigningAlg::Es384 => -35,
+        SigningAlg::Es512 => -36,
+        SigningAlg::Ps256 => -37,
+        SigningAlg::Ps384 => -38,
+        SigningAlg::Ps512 => -39,
+        SigningAlg::Ed25519 => -8,
+    }
+}
+
+fn cose_error(error: c2pa::Error) -> SimpleC2PAError {
+    SimpleC2PAError::SigningFailed {
+        algorithm: X509_SIGNATURE_TYPE.to_owned(),
+        source: error,
+    }
+}
+
+impl IdentityCredentialHolder for X509CredentialHolder {
+    fn get_signature_type(&self) -> String {
+        X509_SIGNATURE_TYPE.to_owned()
+    }
+
+    fn get_reserve_size(&self) -> u32 {
+        self.signer.reserve_size() as u32
+    }
+
+    fn sign(&self, signer_payload: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+        let mut certificates: Vec<Value> = self
+            .signer
+            .certs()
+            .map_err(cose_error)?
+            .into_iter()
+            .map(Value::Bytes)
+            .collect();
+        let x5chain = match certificates.len() {
+            1 => certificates.remove(0),
+            _ => Value::Array(certificates),
+        };
+        let protected_header = serde_cbor::to_vec(&Value::Map(BTreeMap::from([
+            (
+                Value::Integer(COSE_HEADER_ALG),
+                Value::Integer(cose_algorithm(self.signer.alg())),
+            ),
+            (Value::Integer(COSE_HEADER_X5CHAIN), x5chain),
+        ])))?;
+
+        let to_be_signed = serde_cbor::to_vec(&Value::Array(vec![
+            Value::Text("Signature1".to_owned()),
+            Value::Bytes(protected_header.clone()),
+            Value::Bytes(vec![]),
+            Value::Bytes(signer_payload),
+        ]))?;
+        let signature = self.signer.sign(&to_be_signed).map_err(cose_error)?;
+
+        Ok(serde_cbor::to_vec(&Value::Tag(
+            COSE_SIGN1_TAG,
+            Box::new(Value::Array(vec![
+                Value::Bytes(protected_header),
+                Value::Map(BTreeMap::new()),
+                Value::Null,
+                Value::Bytes(signature),
+            ])),
+        ))?)
+    }
+}
+
+/// A reference to an assertion of the claim, by URL and hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashedUri {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    #[serde(with = "serde_bytes")]
+    pub hash: Vec<u8>,
+}
+
+/// The data signed by an identity credential: the assertions it vouches for and the kind of
+/// signature. The signature covers its CBOR encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerPayload {
+    pub referenced_assertions: Vec<HashedUri>,
+    pub sig_type: String,
+}
+
+#[derive(Serialize)]
+struct IdentityAssertion<'a> {
+    signer_payload: &'a SignerPayload,
+    #[serde(serialize_with = "serde_bytes::serialize")]
+    signature: &'a [u8],
+    #[serde(serialize_with = "serde_bytes::serialize")]
+    pad1: &'a [u8],
+    #[serde(serialize_with = "serde_bytes::serialize")]
+    pad2: &'a [u8],
+}
+
+impl IdentityAssertion<'_> {
+    fn to_cbor(&self) -> Result<Vec<u8>, SimpleC2PAError> {
+        Ok(serde_cbor::to_vec(self)?)
+    }
+}
+
+/// Adds the CBOR of an identity assertion to `manifest` as it is, so that its byte strings
+/// stay byte strings.
+fn add_assertion_cbor(manifest: &mut Manifest, assertion: Vec<u8>) -> Result<(), SimpleC2PAError> {
+    manifest.add_assertion(&UserCbor::new(IDENTITY_LABEL, assertion))?;
+    Ok(())
+}
+
+/// Returns the size of a CBOR byte string of `length` bytes, with its header.
+const fn get_byte_string_size(length: usize) -> usize {
+    let header_size = match length {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    };
+    header_size + length
+}
+
+/// Returns the identity assertion padded to exactly `size` bytes. The header of a byte string
+/// grows with its length, so some sizes cannot be reached with `pad1` alone, and `pad2` takes
+/// up the difference.
+fn pad_assertion(
+    signer_payload: &SignerPayload,
+    signature: &[u8],
+    size: usize,
+) -> Result<Vec<u8>, SimpleC2PAError> {
+    let unpadded_size = IdentityAssertion {
+        signer_payload,
+        signature,
+        pad1: &[],
+        pad2: &[],
+    }
+    .to_cbor()?
+    .len();
+    let too_large = || SimpleC2PAError::IdentityAssertionTooLarge {
+        size: unpadded_size,
+        reserved: size,
+    };
+    // The empty paddings take a byte each.
+    let available = (size + 2)
+        .checked_sub(unpadded_size)
+        .ok_or_else(too_large)?;
+    for pad2_length in 0..=2 {
+        let Some(pad1_size) = available.checked_sub(get_byte_string_size(pad2_length)) else {
+            continue;
+        };
+        let pad1_length = [1, 2, 3, 5, 9]
+            .into_iter()
+            .filter_map(|header_size| pad1_size.checked_sub(header_size))
+            .find(|length| get_byte_string_size(*length) == pad1_size);
+        if let Some(pad1_length) = pad1_length {
+            let assertion = IdentityAssertion {
+                signer_payload,
+                signature,
+                pad1: &vec![0; pad1_length],
+                pad2: &vec![0; pad2_length],
+            }
+            .to_cbor()?;
+            if assertion.len() == size {
+                return Ok(assertion);
+            }
+        }
+    }
+    Err(too_large())
+}
+
+/// Creates the CAWG identity assertion of a credential holder.
+///
+/// The signer payload must reference the hard binding of the claim, which is only known once
+/// the asset has been signed. The manifest is therefore signed twice: first with a placeholder
+/// that is large enough for any signature, from which the assertion references are read, then
+/// with the signed assertion padded to the size of the placeholder, so that the layout of the
+/// asset, and with it the hard binding, does not change.
+#[derive(Debug)]
+pub(crate) struct IdentityAssertionBuilder {
+    holder: Arc<dyn IdentityCredentialHolder>,
+    placeholder_size: usize,
+}
+
+impl IdentityAssertionBuilder {
+    pub(crate) fn new(holder: Arc<dyn IdentityCredentialHolder>) -> Self {
+        IdentityAssertionBuilder {
+            holder,
+            placeholder_size: 0,
+        }
+    }
+
+    pub(crate) fn add_placeholder(
+        &mut self,
+        manifest: &mut Manifest,
+    ) -> Result<(), SimpleC2PAError> {
+        let empty_payload = SignerPayload {
+            referenced_assertions: vec![],
+            sig_type: self.holder.get_signature_type(),
+        };
+        let padding_size =
+            self.holder.get_reserve_size() as usize + REFERENCED_ASSERTIONS_RESERVE_SIZE;
+        let placeholder = IdentityAssertion {
+            signer_payload: &empty_payload,
+            signature: &[],
+            pad1: &vec![0; padding_size],
+            pad2: &[],
+        }
+        .to_cbor()?;
+        self.placeholder_size = placeholder.len();
+        add_assertion_cbor(manifest, placeholder)
+    }
+
+    /// Signs the assertions referenced by the claim of the placeholder's manifest store and
+    /// adds the identity assertion to `manifest`.
+    pub(crate) fn add_assertion(
+        &self,
+        manifest: &mut Manifest,
+        references: Vec<AssertionReference>,
+    ) -> Result<(), SimpleC2PAError> {
+        let signer_payload = SignerPayload {
+            referenced_assertions: references
+                .into_iter()
+                .filter(|reference| !reference.label.starts_with(IDENTITY_LABEL))
+                .map(|reference| HashedUri {
+                    url: format!("self#jumbf=c2pa.assertions/{}", reference.label),
+                    alg: reference.alg,
+                    hash: reference.hash,
+                })
+                .collect(),
+            sig_type: self.holder.get_signature_type(),
+        };
+        let signature = self.holder.sign(serde_cbor::to_vec(&signer_payload)?)?;
+        let assertion = pad_assertion(&signer_payload, &signature, self.placeholder_size)?;
+        add_assertion_cbor(manifest, assertion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_signer_payload() -> SignerPayload {
+        SignerPayload {
+            referenced_assertions: vec![HashedUri {
+                url: "self#jumbf=c2pa.assertions/c2pa.hash.data".to_owned(),
+                alg: None,
+                hash: vec![1; 32],
+            }],
+            sig_type: X509_SIGNATURE_TYPE.to_owned(),
+        }
+    }
+
+    #[test]
+    fn pads_to_exact_size() {
+        let signer_payload = create_signer_payload();
+        let signature = vec![2; 100];
+        let unpadded_size = IdentityAssertion {
+            signer_payload: &signer_payload,
+            signature: &signature,
+            pad1: &[],
+            pad2: &[],
+        }
+        .to_cbor()
+        .unwrap()
+        .len();
+        // Around each length where the header of a byte string grows.
+        let sizes = [
+            0, 1, 2, 3, 22, 23, 24, 25, 26, 254, 255, 256, 257, 258, 65535, 65536, 65537,
+        ]
+        .into_iter()
+        .flat_map(|padding| [padding, padding + 1, padding + 2])
+        .map(|padding| unpadded_size + padding);
+        for size in sizes {
+            let assertion = pad_assertion(&signer_payload, &signature, size).unwrap();
+            assert_eq!(assertion.len(), size);
+            let value: Value = serde_cbor::from_slice(&assertion).unwrap();
+            let Value::Map(fields) = value else {
+                panic!("The identity assertion is not a map");
+            };
+            assert_eq!(
+                fields.get(&Value::Text("signature".to_owned())),
+                Some(&Value::Bytes(signature.clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_assertion_larger_than_reserved() {
+        let signer_payload = create_signer_payload();
+        let error = pad_assertion(&signer_payload, &[2; 100], 100).unwrap_err();
+        assert_eq!(error.code(), 402);
+    }
+}
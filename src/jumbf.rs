@@ -0,0 +1,176 @@
+//! Just enough JUMBF (ISO/IEC 19566-5) parsing to read the claim of a manifest store.
+
+use std::result::Result;
+
+use serde_cbor::Value;
+
+use crate::common::SimpleC2PAError;
+
+pub(crate) const TYPE_SUPERBOX: &[u8; 4] = b"jumb";
+const TYPE_DESCRIPTION: &[u8; 4] = b"jumd";
+const TYPE_CBOR: &[u8; 4] = b"cbor";
+const TOGGLE_LABEL: u8 = 0x02;
+const CLAIM_LABEL: &str = "c2pa.claim";
+const ASSERTION_STORE_LABEL: &str = "c2pa.assertions";
+const ASSERTION_STORE_PATH: &str = "c2pa.assertions/";
+
+/// A reference from the claim to one of its assertions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AssertionReference {
+    pub(crate) label: String,
+    pub(crate) alg: Option<String>,
+    pub(crate) hash: Vec<u8>,
+}
+
+/// A box of an ISO base media file, the format JUMBF is built on.
+pub(crate) struct JumbfBox<'a> {
+    pub(crate) box_type: &'a [u8],
+    pub(crate) payload: &'a [u8],
+}
+
+pub(crate) fn read_boxes(mut input: &[u8]) -> Result<Vec<JumbfBox<'_>>, SimpleC2PAError> {
+    let mut boxes = vec![];
+    while !input.is_empty() {
+        if input.len() < 8 {
+            return Err(SimpleC2PAError::InvalidJumbf);
+        }
+        let length = u32::from_be_bytes([input[0], input[1], input[2], input[3]]) as usize;
+        let box_type = &input[4..8];
+        let (header_length, length) = match length {
+            0 => (8, input.len()),
+            1 => {
+                let extended_length = input.get(8..16).ok_or(SimpleC2PAError::InvalidJumbf)?;
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(extended_length);
+                (16, u64::from_be_bytes(bytes) as usize)
+            }
+            length => (8, length),
+        };
+        if length < header_length || length > input.len() {
+            return Err(SimpleC2PAError::InvalidJumbf);
+        }
+
+        boxes.push(JumbfBox {
+            box_type,
+            payload: &input[header_length..length],
+        });
+        input = &input[length..];
+    }
+    Ok(boxes)
+}
+
+/// Returns the label and the remaining boxes of a superbox.
+fn read_superbox<'a>(
+    superbox: &JumbfBox<'a>,
+) -> Result<(String, Vec<JumbfBox<'a>>), SimpleC2PAError> {
+    let mut boxes = read_boxes(superbox.payload)?;
+    if boxes.is_empty() || boxes[0].box_type != TYPE_DESCRIPTION {
+        return Err(SimpleC2PAError::InvalidJumbf);
+    }
+    let description = boxes.remove(0).payload;
+    let toggles = *description.get(16).ok_or(SimpleC2PAError::InvalidJumbf)?;
+    let mut label = String::new();
+    if toggles & TOGGLE_LABEL != 0 {
+        let text = &description[17..];
+        let end = text
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(text.len());
+        label = String::from_utf8_lossy(&text[..end]).into_owned();
+    }
+    Ok((label, boxes))
+}
+
+fn is_superbox(jumbf_box: &&JumbfBox<'_>) -> bool {
+    jumbf_box.box_type == TYPE_SUPERBOX
+}
+
+fn get_text<'a>(map: &'a Value, key: &str) -> Option<&'a Value> {
+    match map {
+        Value::Map(entries) => entries.get(&Value::Text(key.to_owned())),
+        _ => None,
+    }
+}
+
+/// Returns the assertions referenced by the claim of the active manifest, the last one in the
+/// manifest store.
+pub(crate) fn read_assertion_references(
+    manifest_store: &[u8],
+) -> Result<Vec<AssertionReference>, SimpleC2PAError> {
+    let store = read_boxes(manifest_store)?;
+    let store = store.first().ok_or(SimpleC2PAError::InvalidJumbf)?;
+    let (_, manifests) = read_superbox(store)?;
+    let manifest = manifests
+        .iter()
+        .rfind(is_superbox)
+        .ok_or(SimpleC2PAError::InvalidJumbf)?;
+
+    let (_, manifest_boxes) = read_superbox(manifest)?;
+    let mut claim = None;
+    for jumbf_box in manifest_boxes.iter().filter(is_superbox) {
+        let (label, boxes) = read_superbox(jumbf_box)?;
+        if label.starts_with(CLAIM_LABEL) {
+            claim = boxes
+                .into_iter()
+                .find(|claim_box| claim_box.box_type == TYPE_CBOR)
+                .map(|claim_box| claim_box.payload);
+        }
+    }
+    let claim: Value = serde_cbor::from_slice(claim.ok_or(SimpleC2PAError::InvalidJumbf)?)?;
+
+    let Some(Value::Array(assertions)) = get_text(&claim, "assertions") else {
+        return Err(SimpleC2PAError::InvalidJumbf);
+    };
+    let claim_alg = match get_text(&claim, "alg") {
+        Some(Value::Text(alg)) => Some(alg.clone()),
+        _ => None,
+    };
+    let mut references = vec![];
+    for assertion in assertions {
+        let (Some(Value::Text(url)), Some(Value::Bytes(hash))) =
+            (get_text(assertion, "url"), get_text(assertion, "hash"))
+        else {
+            return Err(SimpleC2PAError::InvalidJumbf);
+        };
+        let label = match url.rfind(ASSERTION_STORE_PATH) {
+            Some(index) => &url[index + ASSERTION_STORE_PATH.len()..],
+            None => return Err(SimpleC2PAError::InvalidJumbf),
+        };
+        let alg = match get_text(assertion, "alg") {
+            Some(Value::Text(alg)) => Some(alg.clone()),
+            _ => claim_alg.clone(),
+        };
+        references.push(AssertionReference {
+            label: label.to_owned(),
+            alg,
+            hash: hash.clone(),
+        });
+    }
+    Ok(references)
+}
+
+/// Returns the label of each manifest in the manifest store, with the labels of its
+/// assertions.
+pub(crate) fn read_assertion_labels(
+    manifest_store: &[u8],
+) -> Result<Vec<(String, Vec<String>)>, SimpleC2PAError> {
+    let store = read_boxes(manifest_store)?;
+    let store = store.first().ok_or(SimpleC2PAError::InvalidJumbf)?;
+    let (_, manifests) = read_superbox(store)?;
+
+    let mut labels = vec![];
+    for manifest in manifests.iter().filter(is_superbox) {
+        let (manifest_label, manifest_boxes) = read_superbox(manifest)?;
+        let mut assertion_labels = vec![];
+        for jumbf_box in manifest_boxes.iter().filter(is_superbox) {
+            let (label, assertions) = read_superbox(jumbf_box)?;
+            if label == ASSERTION_STORE_LABEL {
+                for assertion in assertions.iter().filter(is_superbox) {
+                    assertion_labels.push(read_superbox(assertion)?.0);
+                }
+            }
+        }
+        labels.push((manifest_label, assertion_labels));
+    }
+    Ok(labels)
+}
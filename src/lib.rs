@@ -1,13 +1,39 @@
 #![warn(clippy::missing_const_for_fn)]
 
-pub use assertions::{AIDataMiningUsage, CustomAITrainingOptions, ExifData};
+pub use c2pa::{Relationship, SigningAlg};
+
+pub use actions::{ActionData, ActionInfo, ActionRegion, ActionType, DigitalSourceType};
+pub use assertions::{AIDataMiningUsage, AuthorIdentity, CustomAITrainingOptions, ExifData};
+pub use bmff::FragmentedRecording;
 pub use certificates::{
     create_certificate, create_content_credentials_certificate, create_private_key,
-    create_root_certificate, request_signed_certificate, Certificate, CertificateOptions,
+    create_root_certificate, enroll_certificate, import_signed_certificate,
+    request_signed_certificate, Certificate, CertificateAuthority, CertificateOptions,
     CertificateType,
 };
-pub use common::FileData;
+pub use common::{FileData, SimpleC2PAError};
 pub use content_credentials::{ApplicationInfo, ContentCredentials};
+pub use exif_reader::ExifField;
+pub use exif_values::{
+    AltitudeRef, Angle, DirectionRef, Latitude, LatitudeRef, Longitude, LongitudeRef, Rational,
+    SpeedRef,
+};
+pub use identity::{
+    HashedUri, IdentityCredentialHolder, SignerPayload, X509CredentialHolder, X509_SIGNATURE_TYPE,
+};
+pub use location_privacy::{LocationPrivacy, LocationPrivacyReport, LOCATION_LABEL};
+pub use manifest_reader::{
+    AssertionInfo, IngredientInfo, ManifestInfo, ManifestReader, ManifestReport, SignerInfo,
+    ValidationStatusInfo,
+};
+pub use provenance::ProvenanceNode;
+pub use remote_manifest::{HttpManifestFetcher, ManifestFetcher, RemoteManifest};
+pub use resources::Resource;
+pub use signer::{ClaimSigner, LocalSigner};
+pub use thumbnail::{Thumbnail, ThumbnailFormat, ThumbnailOptions};
+pub use timestamp::{
+    HttpTimestampTransport, LocalTimestampAuthority, TimestampAuthority, TimestampTransport,
+};
 
 mod common;
 
@@ -16,3 +42,37 @@ mod certificates;
 mod content_credentials;
 
 mod assertions;
+
+mod exif_reader;
+
+mod exif_values;
+
+mod location_privacy;
+
+mod redaction;
+
+mod actions;
+
+mod manifest_reader;
+
+mod remote_manifest;
+
+mod signer;
+
+mod timestamp;
+
+mod der;
+
+mod identity;
+
+mod jumbf;
+
+mod media_format;
+
+mod bmff;
+
+mod thumbnail;
+
+mod resources;
+
+mod provenance;
@@ -0,0 +1,139 @@
+use std::result::Result;
+
+use crate::assertions::{get_exif_assertion, ExifData};
+use crate::common::SimpleC2PAError;
+use crate::content_credentials::ContentCredentials;
+use crate::exif_reader::ExifField;
+use crate::exif_values::{Latitude, Longitude, Rational};
+
+/// Label of the assertion holding the GPS fields when they are kept apart from `stds.exif`.
+pub const LOCATION_LABEL: &str = "info.guardianproject.location";
+
+/// Meters in a degree of latitude, and at most in a degree of longitude.
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// How precisely the EXIF assertion reveals where the asset was captured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocationPrivacy {
+    /// Writes the GPS fields as they are.
+    Exact,
+    /// Leaves out every `exif:GPS*` field.
+    Remove,
+    /// Rounds the coordinates to the nearest multiple of `precision` degrees, such as `0.01`
+    /// for about a kilometer, and widens the positioning error to match.
+    Coarsen { precision: f64 },
+    /// Writes the GPS fields to a separate [`LOCATION_LABEL`] assertion, so that they can be
+    /// redacted when the asset is used as an ingredient without touching the other EXIF data.
+    SeparateAssertion,
+}
+
+/// What was left out of, or changed in, the `stds.exif` assertion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocationPrivacyReport {
+    /// Names of the fields not written to `stds.exif`, such as `exif:GPSLatitude`.
+    pub removed_fields: Vec<String>,
+    /// Names of the fields written with less precision than given.
+    pub coarsened_fields: Vec<String>,
+    /// Label of the assertion the removed fields were written to instead, if any.
+    pub location_assertion_label: Option<String>,
+}
+
+fn get_names(fields: impl IntoIterator<Item = ExifField>) -> Vec<String> {
+    fields
+        .into_iter()
+        .map(|field| field.get_name().to_owned())
+        .collect()
+}
+
+fn round_to(value: f64, precision: f64) -> f64 {
+    (value / precision).round() * precision
+}
+
+/// Moves the GPS fields of `exif_data` into a new `ExifData`.
+fn take_location<'a>(exif_data: &mut ExifData<'a>) -> ExifData<'a> {
+    let mut location = exif_data.clone();
+    location.retain_fields(&ExifField::GPS);
+    for field in ExifField::GPS {
+        exif_data.clear_field(field);
+    }
+    location
+}
+
+fn coarsen(
+    exif_data: &mut ExifData,
+    precision: f64,
+    report: &mut LocationPrivacyReport,
+) -> Result<(), SimpleC2PAError> {
+    if !precision.is_finite() || precision <= 0.0 || precision > 90.0 {
+        return Err(SimpleC2PAError::InvalidArgument {
+            message: format!("{} is not a valid coordinate precision", precision),
+        });
+    }
+
+    let mut coarsened = vec![];
+    if let Some(latitude) = exif_data.latitude {
+        latitude.to_xmp()?;
+        let degrees = round_to(latitude.to_decimal(), precision).clamp(-90.0, 90.0);
+        exif_data.latitude = Some(Latitude::from_decimal(degrees)?);
+        coarsened.push(ExifField::Latitude);
+    }
+    if let Some(longitude) = exif_data.longitude {
+        longitude.to_xmp()?;
+        let degrees = round_to(longitude.to_decimal(), precision).clamp(-180.0, 180.0);
+        exif_data.longitude = Some(Longitude::from_decimal(degrees)?);
+        coarsened.push(ExifField::Longitude);
+    }
+    if !coarsened.is_empty() {
+        // The coordinates now lie within half a grid cell of the capture location.
+        let error = (precision * METERS_PER_DEGREE * std::f64::consts::FRAC_1_SQRT_2).ceil();
+        let is_less_precise = match exif_data.positioning_error {
+            Some(positioning_error) => positioning_error.to_f64() < error,
+            None => true,
+        };
+        if is_less_precise {
+            exif_data.positioning_error = Some(Rational::new(error as u32, 1));
+            coarsened.push(ExifField::PositioningError);
+        }
+    }
+    report.coarsened_fields = get_names(coarsened);
+    Ok(())
+}
+
+impl ContentCredentials {
+    /// Adds an EXIF assertion, protecting the location where the asset was captured as
+    /// `privacy` asks. Returns what was left out of or changed in the `stds.exif` assertion.
+    pub fn add_exif_assertion_with_location_privacy(
+        &self,
+        mut exif_data: ExifData,
+        privacy: LocationPrivacy,
+    ) -> Result<LocationPrivacyReport, SimpleC2PAError> {
+        let mut report = LocationPrivacyReport::default();
+        let mut location = None;
+        match privacy {
+            LocationPrivacy::Exact => {}
+            LocationPrivacy::Remove => {
+                let removed = take_location(&mut exif_data);
+                report.removed_fields = get_names(removed.get_fields());
+            }
+            LocationPrivacy::Coarsen { precision } => {
+                coarsen(&mut exif_data, precision, &mut report)?;
+            }
+            LocationPrivacy::SeparateAssertion => {
+                let removed = take_location(&mut exif_data);
+                report.removed_fields = get_names(removed.get_fields());
+                if !removed.is_empty() {
+                    report.location_assertion_label = Some(LOCATION_LABEL.to_owned());
+                    location = Some(get_exif_assertion(removed)?);
+                }
+            }
+        }
+
+        let exif = get_exif_assertion(exif_data)?;
+        let mut manifest = self.manifest.lock().unwrap();
+        manifest.add_assertion(&exif)?;
+        if let Some(location) = location {
+            manifest.add_labeled_assertion(LOCATION_LABEL, &location)?;
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,315 @@
+use std::result::Result;
+use std::sync::Arc;
+
+use c2pa::{Ingredient, Manifest, ManifestStore, Relationship, ValidationStatus};
+use serde::Serialize;
+
+use crate::actions::ActionInfo;
+use crate::assertions::CustomAITrainingOptions;
+use crate::common::{FileData, SimpleC2PAError};
+use crate::content_credentials::ContentCredentials;
+use crate::remote_manifest::ManifestFetcher;
+
+const SIGNING_CREDENTIAL_UNTRUSTED: &str = "signingCredential.untrusted";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerInfo {
+    /// Organization named in the subject of the signing certificate.
+    pub subject_organization: Option<String>,
+    pub certificate_serial_number: Option<String>,
+    pub signing_time: Option<String>,
+    pub algorithm: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionInfo {
+    pub label: String,
+    pub json: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestInfo {
+    pub label: Option<String>,
+    pub title: Option<String>,
+    pub format: String,
+    pub instance_id: String,
+    pub claim_generator: String,
+    pub signer: Option<SignerInfo>,
+    pub assertions: Vec<AssertionInfo>,
+    /// MIME type of the thumbnail in the claim, if it has one.
+    pub thumbnail_format: Option<String>,
+    /// JUMBF URI of the thumbnail in the claim, for [`ManifestReader::get_resource`].
+    pub thumbnail_uri: Option<String>,
+    pub ingredients: Vec<IngredientInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngredientInfo {
+    pub title: String,
+    pub format: String,
+    pub instance_id: String,
+    /// `parentOf`, `componentOf` or `inputTo`.
+    pub relationship: String,
+    /// Label of the ingredient's own manifest in the manifest store, if it has one.
+    pub active_manifest: Option<String>,
+    pub thumbnail_format: Option<String>,
+    /// JUMBF URI of the ingredient's thumbnail, for [`ManifestReader::get_resource`].
+    pub thumbnail_uri: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationStatusInfo {
+    pub code: String,
+    pub url: Option<String>,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestReport {
+    pub active_manifest: Option<ManifestInfo>,
+    pub manifests: Vec<ManifestInfo>,
+    pub validation_statuses: Vec<ValidationStatusInfo>,
+    /// No validation failures were reported, apart from the signing
+    /// credential not being on a trust list.
+    pub is_valid: bool,
+    /// The signing credential chains up to a trusted root.
+    pub is_trusted: bool,
+}
+
+/// Returns whether no validation failures were reported, apart from the signing credential
+/// not being on a trust list.
+pub(crate) fn is_valid(validation_statuses: &[ValidationStatusInfo]) -> bool {
+    validation_statuses
+        .iter()
+        .all(|status| status.code == SIGNING_CREDENTIAL_UNTRUSTED)
+}
+
+impl SignerInfo {
+    pub(crate) fn from_manifest(manifest: &Manifest) -> Option<Self> {
+        manifest.signature_info().map(|info| SignerInfo {
+            subject_organization: info.issuer.clone(),
+            certificate_serial_number: info.cert_serial_number.clone(),
+            signing_time: info.time.clone(),
+            algorithm: info.alg.map(|alg| alg.to_string()),
+        })
+    }
+}
+
+impl ManifestInfo {
+    pub(crate) fn from_manifest(manifest: &Manifest) -> Result<Self, SimpleC2PAError> {
+        let mut assertions = vec![];
+        for assertion in manifest.assertions() {
+            assertions.push(AssertionInfo {
+                label: assertion.label().to_owned(),
+                json: serde_json::to_string(assertion.value()?)?,
+            });
+        }
+
+        Ok(ManifestInfo {
+            label: manifest.label().map(str::to_owned),
+            title: manifest.title().map(str::to_owned),
+            format: manifest.format().to_owned(),
+            instance_id: manifest.instance_id().to_owned(),
+            claim_generator: manifest.claim_generator().to_owned(),
+            signer: SignerInfo::from_manifest(manifest),
+            assertions,
+            thumbnail_format: manifest
+                .thumbnail_ref()
+                .map(|thumbnail| thumbnail.format.clone()),
+            thumbnail_uri: manifest
+                .thumbnail_ref()
+                .map(|thumbnail| thumbnail.identifier.clone()),
+            ingredients: manifest
+                .ingredients()
+                .iter()
+                .map(IngredientInfo::from)
+                .collect(),
+        })
+    }
+
+    /// Returns the training and data mining options declared by the manifest, in either the
+    /// `c2pa.training-mining` or the legacy format.
+    pub fn get_ai_training_options(
+        &self,
+    ) -> Result<Option<CustomAITrainingOptions<'static>>, SimpleC2PAError> {
+        CustomAITrainingOptions::from_assertions(&self.assertions)
+    }
+
+    /// Returns the actions recorded in the manifest.
+    pub fn get_actions(&self) -> Result<Vec<ActionInfo>, SimpleC2PAError> {
+        ActionInfo::from_assertions(&self.assertions)
+    }
+}
+
+/// Returns the name of a relationship as it appears in the manifest, such as `parentOf`.
+pub(crate) const fn get_relationship_name(relationship: &Relationship) -> &'static str {
+    match relationship {
+        Relationship::ParentOf => "parentOf",
+        Relationship::ComponentOf => "componentOf",
+        Relationship::InputTo => "inputTo",
+    }
+}
+
+impl From<&Ingredient> for IngredientInfo {
+    fn from(ingredient: &Ingredient) -> Self {
+        IngredientInfo {
+            title: ingredient.title().to_owned(),
+            format: ingredient.format().to_owned(),
+            instance_id: ingredient.instance_id().to_owned(),
+            relationship: get_relationship_name(ingredient.relationship()).to_owned(),
+            active_manifest: ingredient.active_manifest().map(str::to_owned),
+            thumbnail_format: ingredient
+                .thumbnail_ref()
+                .map(|thumbnail| thumbnail.format.clone()),
+            thumbnail_uri: ingredient
+                .thumbnail_ref()
+                .map(|thumbnail| thumbnail.identifier.clone()),
+        }
+    }
+}
+
+impl From<&ValidationStatus> for ValidationStatusInfo {
+    fn from(status: &ValidationStatus) -> Self {
+        ValidationStatusInfo {
+            code: status.code().to_owned(),
+            url: status.url().map(str::to_owned),
+            explanation: status.explanation().map(str::to_owned),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ManifestReader {
+    pub(crate) manifest_store: ManifestStore,
+}
+
+impl ManifestReader {
+    /// Reads the manifest embedded in `file`. The URL of a remote manifest comes from the
+    /// asset, so it is never fetched: an asset that only references one fails with
+    /// [`SimpleC2PAError::RemoteManifest`], and can be read with
+    /// [`ManifestReader::new_with_fetcher`] instead.
+    pub fn new(file: Arc<FileData>) -> Result<Arc<Self>, SimpleC2PAError> {
+        Self::from_file(file, None)
+    }
+
+    /// Reads the manifest embedded in `file`, resolving remote manifest references with
+    /// `fetcher`, such as an [`HttpManifestFetcher`](crate::HttpManifestFetcher).
+    pub fn new_with_fetcher(
+        file: Arc<FileData>,
+        fetcher: Arc<dyn ManifestFetcher>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        Self::from_file(file, Some(fetcher.as_ref()))
+    }
+
+    fn from_file(
+        file: Arc<FileData>,
+        fetcher: Option<&dyn ManifestFetcher>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        let format = file.get_mime_type()?;
+        let manifest_store =
+            match ManifestStore::from_stream(&format, &mut file.get_reader()?, true) {
+                Err(c2pa::Error::RemoteManifestUrl(url)) => {
+                    let Some(fetcher) = fetcher else {
+                        return Err(SimpleC2PAError::RemoteManifest {
+                            url,
+                            message: "Remote manifests are only fetched with a fetcher".to_owned(),
+                        });
+                    };
+                    let manifest_bytes = fetcher.fetch(url)?;
+                    ManifestStore::from_manifest_and_asset_bytes(
+                        &manifest_bytes,
+                        &format,
+                        &file.get_bytes()?,
+                    )?
+                }
+                result => result?,
+            };
+
+        Ok(Arc::new(ManifestReader { manifest_store }))
+    }
+
+    /// Reads the manifest store from a `.c2pa` sidecar and validates `file` against it.
+    pub fn with_sidecar(
+        file: Arc<FileData>,
+        sidecar: Arc<FileData>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        let format = file.get_mime_type()?;
+        let manifest_store = ManifestStore::from_manifest_and_asset_bytes(
+            &sidecar.get_bytes()?,
+            &format,
+            &file.get_bytes()?,
+        )?;
+
+        Ok(Arc::new(ManifestReader { manifest_store }))
+    }
+
+    pub fn get_report(&self) -> Result<ManifestReport, SimpleC2PAError> {
+        let active_manifest = match self.manifest_store.get_active() {
+            Some(manifest) => Some(ManifestInfo::from_manifest(manifest)?),
+            None => None,
+        };
+
+        let mut manifests = vec![];
+        for manifest in self.manifest_store.manifests().values() {
+            manifests.push(ManifestInfo::from_manifest(manifest)?);
+        }
+
+        let validation_statuses: Vec<ValidationStatusInfo> = self
+            .manifest_store
+            .validation_status()
+            .unwrap_or_default()
+            .iter()
+            .map(ValidationStatusInfo::from)
+            .collect();
+        let is_trusted = !validation_statuses
+            .iter()
+            .any(|status| status.code == SIGNING_CREDENTIAL_UNTRUSTED);
+        let is_valid = is_valid(&validation_statuses);
+
+        Ok(ManifestReport {
+            active_manifest,
+            manifests,
+            validation_statuses,
+            is_valid,
+            is_trusted,
+        })
+    }
+
+    pub fn verify(&self) -> Result<(), SimpleC2PAError> {
+        let report = self.get_report()?;
+        if report.active_manifest.is_none() {
+            return Err(SimpleC2PAError::ManifestNotFound);
+        }
+        if !report.is_valid {
+            return Err(SimpleC2PAError::ValidationFailed {
+                codes: report
+                    .validation_statuses
+                    .into_iter()
+                    .map(|status| status.code)
+                    .collect(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ContentCredentials {
+    pub fn read(file: Arc<FileData>) -> Result<ManifestReport, SimpleC2PAError> {
+        ManifestReader::new(file)?.get_report()
+    }
+
+    /// Reads the manifest of an asset published with a `.c2pa` sidecar from
+    /// [`ContentCredentials::export_manifest`].
+    pub fn read_with_sidecar(
+        file: Arc<FileData>,
+        sidecar: Arc<FileData>,
+    ) -> Result<ManifestReport, SimpleC2PAError> {
+        ManifestReader::with_sidecar(file, sidecar)?.get_report()
+    }
+}
@@ -0,0 +1,201 @@
+//! Detection of the media formats c2pa can embed manifests in.
+
+/// A media format, with the extensions it is known by. Formats in the same family cannot
+/// be told apart by their content, so the file name decides between them.
+struct MediaFormat {
+    mime_type: &'static str,
+    extensions: &'static [&'static str],
+    family: &'static str,
+}
+
+const FORMATS: &[MediaFormat] = &[
+    MediaFormat {
+        mime_type: "image/jpeg",
+        extensions: &["jpg", "jpeg"],
+        family: "jpeg",
+    },
+    MediaFormat {
+        mime_type: "image/png",
+        extensions: &["png"],
+        family: "png",
+    },
+    MediaFormat {
+        mime_type: "image/webp",
+        extensions: &["webp"],
+        family: "webp",
+    },
+    MediaFormat {
+        mime_type: "image/heic",
+        extensions: &["heic"],
+        family: "heif",
+    },
+    MediaFormat {
+        mime_type: "image/heif",
+        extensions: &["heif"],
+        family: "heif",
+    },
+    MediaFormat {
+        mime_type: "image/avif",
+        extensions: &["avif"],
+        family: "avif",
+    },
+    MediaFormat {
+        mime_type: "image/tiff",
+        extensions: &["tif", "tiff"],
+        family: "tiff",
+    },
+    MediaFormat {
+        mime_type: "image/x-adobe-dng",
+        extensions: &["dng"],
+        family: "tiff",
+    },
+    MediaFormat {
+        mime_type: "image/svg+xml",
+        extensions: &["svg"],
+        family: "svg",
+    },
+    MediaFormat {
+        mime_type: "video/mp4",
+        extensions: &["mp4"],
+        family: "mp4",
+    },
+    MediaFormat {
+        mime_type: "audio/mp4",
+        extensions: &["m4a"],
+        family: "mp4",
+    },
+    MediaFormat {
+        mime_type: "video/quicktime",
+        extensions: &["mov"],
+        family: "quicktime",
+    },
+    MediaFormat {
+        mime_type: "video/x-msvideo",
+        extensions: &["avi"],
+        family: "avi",
+    },
+    MediaFormat {
+        mime_type: "audio/wav",
+        extensions: &["wav"],
+        family: "wav",
+    },
+    MediaFormat {
+        mime_type: "audio/mpeg",
+        extensions: &["mp3"],
+        family: "mp3",
+    },
+];
+
+/// Bytes of the file read for detection, enough to find the root element of an SVG.
+pub(crate) const HEADER_SIZE: usize = 1024;
+
+fn find_by_mime_type(mime_type: &str) -> Option<&'static MediaFormat> {
+    FORMATS.iter().find(|format| format.mime_type == mime_type)
+}
+
+fn find_by_extension(extension: &str) -> Option<&'static MediaFormat> {
+    let extension = extension.to_lowercase();
+    FORMATS
+        .iter()
+        .find(|format| format.extensions.contains(&extension.as_str()))
+}
+
+/// Returns the MIME type of an ISO base media file from its major brand.
+fn detect_bmff(brand: &[u8]) -> &'static str {
+    match brand {
+        b"heic" | b"heix" | b"hevc" | b"hevx" | b"heim" | b"heis" => "image/heic",
+        b"mif1" | b"msf1" => "image/heif",
+        b"avif" | b"avis" => "image/avif",
+        b"qt  " => "video/quicktime",
+        b"M4A " | b"M4B " => "audio/mp4",
+        _ => "video/mp4",
+    }
+}
+
+/// Returns whether the header starts with the header of an MPEG audio frame. The version,
+/// layer, bitrate and sample rate are checked, since the frame sync alone matches other
+/// content, such as the byte order mark of UTF-16LE text.
+fn is_mpeg_audio_frame(header: &[u8]) -> bool {
+    let [0xff, second, third, ..] = *header else {
+        return false;
+    };
+    let version = (second >> 3) & 0x03;
+    let layer = (second >> 1) & 0x03;
+    let bitrate = third >> 4;
+    let sample_rate = (third >> 2) & 0x03;
+    second & 0xe0 == 0xe0
+        && version != 0x01
+        && layer != 0x00
+        && bitrate != 0x0f
+        && sample_rate != 0x03
+}
+
+/// Decodes the header as UTF-16 when it starts with a UTF-16 byte order mark, and as UTF-8
+/// otherwise.
+fn decode_text(header: &[u8]) -> String {
+    let decode_utf16 = |to_unit: fn([u8; 2]) -> u16| {
+        let units = header[2..]
+            .chunks_exact(2)
+            .map(|unit| to_unit([unit[0], unit[1]]));
+        char::decode_utf16(units)
+            .map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    };
+    match header {
+        [0xff, 0xfe, ..] => decode_utf16(u16::from_le_bytes),
+        [0xfe, 0xff, ..] => decode_utf16(u16::from_be_bytes),
+        _ => String::from_utf8_lossy(header).into_owned(),
+    }
+}
+
+fn is_svg(header: &[u8]) -> bool {
+    let text = decode_text(header);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    (text.starts_with("<?xml") || text.starts_with("<svg") || text.starts_with("<!--"))
+        && text.contains("<svg")
+}
+
+/// Returns the MIME type of a file from its first bytes.
+fn detect_mime_type(header: &[u8]) -> Option<&'static str> {
+    let mime_type = match header {
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => "image/png",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => "audio/wav",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'A', b'V', b'I', b' ', ..] => "video/x-msvideo",
+        [b'I', b'I', 0x2a, 0x00, ..] | [b'M', b'M', 0x00, 0x2a, ..] => "image/tiff",
+        [_, _, _, _, b'f', b't', b'y', b'p', brand @ ..] if brand.len() >= 4 => {
+            detect_bmff(&brand[..4])
+        }
+        [b'I', b'D', b'3', ..] => "audio/mpeg",
+        header if is_mpeg_audio_frame(header) => "audio/mpeg",
+        header if is_svg(header) => "image/svg+xml",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// Returns the usual extension of files of a supported format.
+pub(crate) fn get_extension(mime_type: &str) -> Option<&'static str> {
+    find_by_mime_type(mime_type).map(|format| format.extensions[0])
+}
+
+/// Returns the MIME types of the supported formats.
+pub(crate) fn get_supported_mime_types() -> Vec<String> {
+    FORMATS
+        .iter()
+        .map(|format| format.mime_type.to_owned())
+        .collect()
+}
+
+/// Returns the MIME type of a file from its first bytes, using the extension of its name
+/// to choose between formats that look the same, or when the content is not recognized.
+pub(crate) fn get_mime_type(header: &[u8], extension: Option<&str>) -> Option<&'static str> {
+    let detected = detect_mime_type(header).and_then(find_by_mime_type);
+    let named = extension.and_then(find_by_extension);
+    match (detected, named) {
+        (Some(detected), Some(named)) if detected.family == named.family => Some(named.mime_type),
+        (Some(detected), _) => Some(detected.mime_type),
+        (None, named) => named.map(|format| format.mime_type),
+    }
+}
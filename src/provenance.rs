@@ -0,0 +1,218 @@
+//! The provenance of an asset as a tree, from the asset down through the ingredients of each
+//! manifest in its store.
+
+use std::result::Result;
+use std::sync::Arc;
+
+use c2pa::{Ingredient, Manifest, ManifestStore};
+use serde::Serialize;
+
+use crate::actions::ActionInfo;
+use crate::common::{FileData, SimpleC2PAError};
+use crate::content_credentials::ContentCredentials;
+use crate::manifest_reader::{
+    get_relationship_name, is_valid, ManifestInfo, ManifestReader, SignerInfo, ValidationStatusInfo,
+};
+
+/// An asset in the provenance tree: the asset that was read, or one of the ingredients of the
+/// node above it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvenanceNode {
+    pub title: Option<String>,
+    pub format: String,
+    pub instance_id: String,
+    /// How the node above used this asset, such as `parentOf`. `None` for the asset that was
+    /// read.
+    pub relationship: Option<String>,
+    /// Label of the asset's manifest, `None` for an ingredient without content credentials.
+    pub manifest_label: Option<String>,
+    pub claim_generator: Option<String>,
+    pub signer: Option<SignerInfo>,
+    pub actions: Vec<ActionInfo>,
+    /// JUMBF URI of the thumbnail, for [`ManifestReader::get_resource`].
+    pub thumbnail_uri: Option<String>,
+    /// Validation results concerning this asset's manifest.
+    pub validation_statuses: Vec<ValidationStatusInfo>,
+    /// No validation failures were reported for this asset, apart from the signing credential
+    /// not being on a trust list.
+    pub is_valid: bool,
+    pub ingredients: Vec<ProvenanceNode>,
+}
+
+impl ProvenanceNode {
+    /// Returns the chain of parents from the oldest version of the asset to this one, such as
+    /// the capture, the crop and the published image.
+    pub fn get_history(&self) -> Vec<&ProvenanceNode> {
+        let mut history = vec![self];
+        let mut node = self;
+        while let Some(parent) = node
+            .ingredients
+            .iter()
+            .find(|ingredient| ingredient.relationship.as_deref() == Some("parentOf"))
+        {
+            history.push(parent);
+            node = parent;
+        }
+        history.reverse();
+        history
+    }
+}
+
+fn add_statuses(
+    validation_statuses: &mut Vec<ValidationStatusInfo>,
+    statuses: impl IntoIterator<Item = ValidationStatusInfo>,
+) {
+    for status in statuses {
+        if !validation_statuses.contains(&status) {
+            validation_statuses.push(status);
+        }
+    }
+}
+
+struct ProvenanceBuilder<'a> {
+    manifest_store: &'a ManifestStore,
+    validation_statuses: Vec<ValidationStatusInfo>,
+}
+
+impl ProvenanceBuilder<'_> {
+    /// Returns the statuses of the store that refer to the manifest with `label`.
+    fn get_statuses(&self, label: &str) -> Vec<ValidationStatusInfo> {
+        self.validation_statuses
+            .iter()
+            .filter(|status| status.url.as_ref().is_some_and(|url| url.contains(label)))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the statuses of the store that refer to no manifest in it, such as a mismatch
+    /// between the asset and its hard binding.
+    fn get_unattributed_statuses(&self) -> Vec<ValidationStatusInfo> {
+        let labels: Vec<&String> = self.manifest_store.manifests().keys().collect();
+        self.validation_statuses
+            .iter()
+            .filter(|status| match &status.url {
+                Some(url) => !labels.iter().any(|label| url.contains(label.as_str())),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the node of the manifest with `label`. `ancestors` holds the labels of the
+    /// manifests above it, so that a store referring back to one of them cannot loop.
+    fn build_manifest_node(
+        &self,
+        label: &str,
+        manifest: &Manifest,
+        ancestors: &mut Vec<String>,
+    ) -> Result<ProvenanceNode, SimpleC2PAError> {
+        let info = ManifestInfo::from_manifest(manifest)?;
+        ancestors.push(label.to_owned());
+        let mut ingredients = vec![];
+        for ingredient in manifest.ingredients() {
+            ingredients.push(self.build_ingredient_node(ingredient, ancestors)?);
+        }
+        ancestors.pop();
+
+        let actions = info.get_actions()?;
+        let validation_statuses = self.get_statuses(label);
+        Ok(ProvenanceNode {
+            title: info.title,
+            format: info.format,
+            instance_id: info.instance_id,
+            relationship: None,
+            manifest_label: Some(label.to_owned()),
+            claim_generator: Some(info.claim_generator),
+            signer: info.signer,
+            actions,
+            thumbnail_uri: info.thumbnail_uri,
+            is_valid: is_valid(&validation_statuses),
+            validation_statuses,
+            ingredients,
+        })
+    }
+
+    fn build_ingredient_node(
+        &self,
+        ingredient: &Ingredient,
+        ancestors: &mut Vec<String>,
+    ) -> Result<ProvenanceNode, SimpleC2PAError> {
+        let label = ingredient.active_manifest();
+        let manifest = label
+            .filter(|label| !ancestors.iter().any(|ancestor| ancestor == label))
+            .and_then(|label| self.manifest_store.get(label));
+        let mut node = match (label, manifest) {
+            (Some(label), Some(manifest)) => {
+                self.build_manifest_node(label, manifest, ancestors)?
+            }
+            _ => ProvenanceNode {
+                title: None,
+                format: ingredient.format().to_owned(),
+                instance_id: ingredient.instance_id().to_owned(),
+                relationship: None,
+                manifest_label: label.map(str::to_owned),
+                claim_generator: None,
+                signer: None,
+                actions: vec![],
+                thumbnail_uri: None,
+                validation_statuses: vec![],
+                is_valid: true,
+                ingredients: vec![],
+            },
+        };
+
+        node.title = node.title.or_else(|| Some(ingredient.title().to_owned()));
+        node.relationship = Some(get_relationship_name(ingredient.relationship()).to_owned());
+        // The thumbnail the ingredient was given when it was used, over that of its manifest.
+        if let Some(thumbnail) = ingredient.thumbnail_ref() {
+            node.thumbnail_uri = Some(thumbnail.identifier.clone());
+        }
+        // The statuses recorded when the ingredient was validated on being added.
+        let statuses = ingredient.validation_status().unwrap_or_default();
+        add_statuses(
+            &mut node.validation_statuses,
+            statuses.iter().map(ValidationStatusInfo::from),
+        );
+        node.is_valid = is_valid(&node.validation_statuses);
+        Ok(node)
+    }
+}
+
+impl ManifestReader {
+    /// Returns the provenance of the asset as a tree, with a node for the active manifest
+    /// and, below it, for each of its ingredients, their own ingredients and so on.
+    pub fn get_provenance(&self) -> Result<ProvenanceNode, SimpleC2PAError> {
+        let (Some(label), Some(manifest)) = (
+            self.manifest_store.active_label(),
+            self.manifest_store.get_active(),
+        ) else {
+            return Err(SimpleC2PAError::ManifestNotFound);
+        };
+        let builder = ProvenanceBuilder {
+            manifest_store: &self.manifest_store,
+            validation_statuses: self
+                .manifest_store
+                .validation_status()
+                .unwrap_or_default()
+                .iter()
+                .map(ValidationStatusInfo::from)
+                .collect(),
+        };
+
+        let mut root = builder.build_manifest_node(label, manifest, &mut vec![])?;
+        add_statuses(
+            &mut root.validation_statuses,
+            builder.get_unattributed_statuses(),
+        );
+        root.is_valid = is_valid(&root.validation_statuses);
+        Ok(root)
+    }
+}
+
+impl ContentCredentials {
+    /// Reads the provenance of `file` as a tree; see [`ManifestReader::get_provenance`].
+    pub fn read_provenance(file: Arc<FileData>) -> Result<ProvenanceNode, SimpleC2PAError> {
+        ManifestReader::new(file)?.get_provenance()
+    }
+}
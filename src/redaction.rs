@@ -0,0 +1,127 @@
+use std::result::Result;
+
+use serde_json::Value;
+
+use crate::actions::{ActionData, ActionType};
+use crate::common::SimpleC2PAError;
+use crate::content_credentials::ContentCredentials;
+use crate::jumbf::read_assertion_labels;
+
+const JUMBF_PREFIX: &str = "self#jumbf=";
+const ASSERTION_STORE_PATH: &str = "/c2pa.assertions/";
+/// Assertions the claim cannot do without: the history of the asset and its hard bindings.
+const NON_REDACTABLE_PREFIXES: [&str; 2] = ["c2pa.actions", "c2pa.hash."];
+
+/// Returns the URI of an assertion in a manifest store, in the form c2pa expects for
+/// redactions.
+fn get_assertion_uri(manifest_label: &str, assertion_label: &str) -> String {
+    format!(
+        "{}/c2pa/{}{}{}",
+        JUMBF_PREFIX, manifest_label, ASSERTION_STORE_PATH, assertion_label
+    )
+}
+
+/// Splits a URI such as `self#jumbf=c2pa/urn:uuid:.../c2pa.assertions/stds.exif` into its
+/// manifest and assertion labels.
+fn parse_assertion_uri(uri: &str) -> Option<(&str, &str)> {
+    let path = uri.strip_prefix(JUMBF_PREFIX)?;
+    let path = path
+        .strip_prefix('/')
+        .unwrap_or(path)
+        .strip_prefix("c2pa/")?;
+    let (manifest_label, assertion_label) = path.split_once(ASSERTION_STORE_PATH)?;
+    (!manifest_label.is_empty() && !assertion_label.is_empty())
+        .then_some((manifest_label, assertion_label))
+}
+
+fn invalid_redaction(assertion: &str, message: &str) -> SimpleC2PAError {
+    SimpleC2PAError::InvalidRedaction {
+        assertion: assertion.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+impl ContentCredentials {
+    /// Removes assertions, such as `stds.exif` with the capture location or the author's
+    /// CreativeWork, from the manifest store of the parent ingredient, and records a
+    /// `c2pa.redacted` action with `reason` for each.
+    ///
+    /// Each entry is either the label of an assertion in the parent's active manifest or the
+    /// JUMBF URI of an assertion in any manifest of its store. Actions and hash assertions
+    /// cannot be redacted. The parent's thumbnail is kept unless it is removed with
+    /// [`ContentCredentials::set_ingredient_thumbnail`]. Returns the URIs of the redacted
+    /// assertions.
+    pub fn redact_parent_assertions(
+        &self,
+        assertions: Vec<String>,
+        reason: Option<String>,
+    ) -> Result<Vec<String>, SimpleC2PAError> {
+        let mut manifest = self.manifest.lock().unwrap();
+        let (instance_id, uris) = {
+            let no_manifest = || {
+                invalid_redaction(
+                    &assertions.join(", "),
+                    "the parent ingredient has no manifest",
+                )
+            };
+            let parent = manifest
+                .ingredients()
+                .iter()
+                .find(|ingredient| ingredient.is_parent())
+                .ok_or_else(no_manifest)?;
+            let active_manifest = parent.active_manifest().ok_or_else(no_manifest)?;
+            let instance_id = parent.instance_id().to_owned();
+            let manifest_store = parent.manifest_data().ok_or_else(no_manifest)?;
+            let labels = read_assertion_labels(&manifest_store)?;
+
+            let mut uris = vec![];
+            for assertion in &assertions {
+                let (manifest_label, assertion_label) = if assertion.starts_with(JUMBF_PREFIX) {
+                    parse_assertion_uri(assertion)
+                        .ok_or_else(|| invalid_redaction(assertion, "not an assertion URI"))?
+                } else {
+                    (active_manifest, assertion.as_str())
+                };
+                if NON_REDACTABLE_PREFIXES
+                    .iter()
+                    .any(|prefix| assertion_label.starts_with(prefix))
+                {
+                    return Err(invalid_redaction(
+                        assertion,
+                        "actions and hash assertions cannot be redacted",
+                    ));
+                }
+                let exists = labels.iter().any(|(label, assertion_labels)| {
+                    label == manifest_label
+                        && assertion_labels
+                            .iter()
+                            .any(|label| label == assertion_label)
+                });
+                if !exists {
+                    return Err(invalid_redaction(
+                        assertion,
+                        "not found in the parent ingredient's manifest store",
+                    ));
+                }
+                uris.push(get_assertion_uri(manifest_label, assertion_label));
+            }
+            (instance_id, uris)
+        };
+
+        for uri in &uris {
+            manifest.add_redaction(uri.as_str())?;
+        }
+        drop(manifest);
+
+        for uri in &uris {
+            let mut action = ActionData::new(ActionType::Redacted);
+            action
+                .parameters
+                .insert("redacted".to_owned(), Value::String(uri.clone()));
+            action.ingredient_instance_id = Some(instance_id.clone());
+            action.reason = reason.clone();
+            self.add_action(action)?;
+        }
+        Ok(uris)
+    }
+}
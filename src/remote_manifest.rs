@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+use std::io::Read;
+use std::result::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::{FileData, SimpleC2PAError};
+
+/// Time allowed for a request, from connecting to reading the whole response, in seconds.
+pub(crate) const DEFAULT_TIMEOUT: u64 = 30;
+/// Largest manifest store accepted, well above that of a manifest with a few thumbnails.
+const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Retrieves manifest stores referenced by URL from assets signed with
+/// [`ContentCredentials::embed_remote_manifest`](crate::ContentCredentials::embed_remote_manifest).
+pub trait ManifestFetcher: Send + Sync + Debug {
+    /// Returns the manifest store published at `url`.
+    fn fetch(&self, url: String) -> Result<Vec<u8>, SimpleC2PAError>;
+}
+
+/// Fetches manifest stores over HTTP. The URL comes from the asset being read, so requests
+/// time out and responses are limited in size.
+#[derive(Debug)]
+pub struct HttpManifestFetcher {
+    agent: ureq::Agent,
+    max_size: u64,
+}
+
+impl HttpManifestFetcher {
+    pub fn new() -> Arc<Self> {
+        Arc::new(HttpManifestFetcher::default())
+    }
+
+    /// Fetches manifest stores of at most `max_size` bytes, allowing `timeout_seconds` for
+    /// each request, from connecting to reading the whole response.
+    pub fn new_with_limits(timeout_seconds: u64, max_size: u64) -> Arc<Self> {
+        Arc::new(HttpManifestFetcher::with_limits(timeout_seconds, max_size))
+    }
+
+    fn with_limits(timeout_seconds: u64, max_size: u64) -> Self {
+        // Connections are not reused: one whose response was cut off at `max_size` still
+        // holds the rest of it, and reading the next response from it never times out.
+        HttpManifestFetcher {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(timeout_seconds))
+                .max_idle_connections(0)
+                .build(),
+            max_size,
+        }
+    }
+}
+
+impl Default for HttpManifestFetcher {
+    fn default() -> Self {
+        HttpManifestFetcher::with_limits(DEFAULT_TIMEOUT, DEFAULT_MAX_SIZE)
+    }
+}
+
+impl ManifestFetcher for HttpManifestFetcher {
+    fn fetch(&self, url: String) -> Result<Vec<u8>, SimpleC2PAError> {
+        let response = self
+            .agent
+            .get(&url)
+            .set("Accept", "application/c2pa")
+            .call()
+            .map_err(|error| SimpleC2PAError::RemoteManifest {
+                url: url.clone(),
+                message: error.to_string(),
+            })?;
+        let mut body = vec![];
+        response
+            .into_reader()
+            .take(self.max_size + 1)
+            .read_to_end(&mut body)?;
+        if body.len() as u64 > self.max_size {
+            return Err(SimpleC2PAError::RemoteManifest {
+                url,
+                message: format!("The manifest store is larger than {} bytes", self.max_size),
+            });
+        }
+        Ok(body)
+    }
+}
+
+/// An asset that references its manifest by URL, together with the manifest store to publish
+/// at that URL.
+#[derive(Debug, Clone)]
+pub struct RemoteManifest {
+    pub url: String,
+    pub asset: Arc<FileData>,
+    pub manifest_store: Arc<FileData>,
+}
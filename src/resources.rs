@@ -0,0 +1,207 @@
+//! Thumbnails, icons and other data stored in the manifest store next to the manifests.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::sync::Arc;
+
+use crate::common::{FileData, SimpleC2PAError};
+use crate::manifest_reader::ManifestReader;
+use crate::media_format::{get_extension, get_mime_type, HEADER_SIZE};
+
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+/// A resource of the manifest store, such as the thumbnail of the claim or of an ingredient.
+#[derive(Debug, Clone)]
+pub struct Resource {
+    /// The JUMBF URI of the resource, such as
+    /// `self#jumbf=c2pa/urn:uuid:.../c2pa.assertions/c2pa.thumbnail.claim.jpeg`.
+    pub uri: String,
+    /// Label of the manifest the resource belongs to.
+    pub manifest_label: String,
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Returns the name of a resource file: the last component of its URI, with characters that
+/// are not allowed in file names on every platform replaced.
+fn get_file_name(uri: &str, mime_type: &str) -> String {
+    let name = uri.rsplit(['/', '=']).next().unwrap_or(uri);
+    let file_name = sanitize(name);
+    match get_extension(mime_type) {
+        Some(extension) if Path::new(name).extension().is_none() => {
+            format!("{}.{}", file_name, extension)
+        }
+        _ => file_name,
+    }
+}
+
+/// Returns `name` as a single file name. Names that are empty or only dots, such as `..`,
+/// have their dots replaced as well, so that they cannot refer to a directory.
+fn sanitize(name: &str) -> String {
+    let only_dots = name.chars().all(|character| character == '.');
+    let name: String = name
+        .chars()
+        .map(|character| match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => character,
+            '.' if !only_dots => character,
+            _ => '_',
+        })
+        .collect();
+    if name.is_empty() {
+        return "_".to_owned();
+    }
+    name
+}
+
+/// Creates the directory of the resources of the manifest with `label` in `output_dir` and
+/// returns it, checking that it is inside `output_dir`, even through symbolic links.
+fn create_manifest_dir(output_dir: &Path, label: &str) -> Result<PathBuf, SimpleC2PAError> {
+    let manifest_dir = output_dir.join(sanitize(label));
+    fs::create_dir_all(&manifest_dir)?;
+    let canonical_dir = fs::canonicalize(&manifest_dir)?;
+    if canonical_dir.parent() != Some(fs::canonicalize(output_dir)?.as_path()) {
+        return Err(SimpleC2PAError::InvalidArgument {
+            message: format!("Manifest label {} is not a directory name", label),
+        });
+    }
+    Ok(manifest_dir)
+}
+
+fn create_resource(
+    formats: &HashMap<String, String>,
+    manifest_label: &str,
+    uri: &str,
+    bytes: &[u8],
+) -> Resource {
+    // Resources that no manifest describes, such as icons, are recognized by their content.
+    let mime_type = formats.get(uri).cloned().unwrap_or_else(|| {
+        let extension = Path::new(uri)
+            .extension()
+            .map(|extension| extension.to_string_lossy());
+        get_mime_type(&bytes[..bytes.len().min(HEADER_SIZE)], extension.as_deref())
+            .unwrap_or(DEFAULT_MIME_TYPE)
+            .to_owned()
+    });
+    Resource {
+        uri: uri.to_owned(),
+        manifest_label: manifest_label.to_owned(),
+        mime_type,
+        bytes: bytes.to_vec(),
+    }
+}
+
+impl ManifestReader {
+    /// Returns the MIME types that the manifests give for their resources, by URI.
+    fn get_resource_formats(&self) -> HashMap<String, String> {
+        let mut formats = HashMap::new();
+        for manifest in self.manifest_store.manifests().values() {
+            let references = manifest.thumbnail_ref().into_iter().chain(
+                manifest
+                    .ingredients()
+                    .iter()
+                    .filter_map(|ingredient| ingredient.thumbnail_ref()),
+            );
+            for reference in references {
+                formats.insert(reference.identifier.clone(), reference.format.clone());
+            }
+        }
+        formats
+    }
+
+    /// Returns the URIs of the resources of every manifest in the store, sorted by manifest.
+    pub fn get_resource_uris(&self) -> Vec<String> {
+        let mut uris: Vec<(&String, &String)> = vec![];
+        for (label, manifest) in self.manifest_store.manifests() {
+            uris.extend(
+                manifest
+                    .resources()
+                    .resources()
+                    .keys()
+                    .map(|uri| (label, uri)),
+            );
+        }
+        uris.sort();
+        uris.into_iter().map(|(_, uri)| uri.clone()).collect()
+    }
+
+    /// Returns the resource with `uri`, as found in [`ManifestInfo::thumbnail_uri`] and
+    /// [`IngredientInfo::thumbnail_uri`].
+    ///
+    /// [`ManifestInfo::thumbnail_uri`]: crate::ManifestInfo::thumbnail_uri
+    /// [`IngredientInfo::thumbnail_uri`]: crate::IngredientInfo::thumbnail_uri
+    pub fn get_resource(&self, uri: String) -> Result<Resource, SimpleC2PAError> {
+        for (label, manifest) in self.manifest_store.manifests() {
+            if let Some(bytes) = manifest.resources().resources().get(&uri) {
+                return Ok(create_resource(
+                    &self.get_resource_formats(),
+                    label,
+                    &uri,
+                    bytes,
+                ));
+            }
+        }
+        Err(SimpleC2PAError::ResourceNotFound { uri })
+    }
+
+    /// Writes every resource of the manifest store to `output_dir`, in a directory per manifest
+    /// named after its label, and returns the files written. The file names are taken from the
+    /// URIs, with an extension added for the MIME type when they have none.
+    pub fn write_resources(
+        &self,
+        output_dir: PathBuf,
+    ) -> Result<Vec<Arc<FileData>>, SimpleC2PAError> {
+        let formats = self.get_resource_formats();
+        let manifests: BTreeMap<_, _> = self.manifest_store.manifests().iter().collect();
+        let mut files = vec![];
+        for (label, manifest) in manifests {
+            let resources: BTreeMap<_, _> = manifest.resources().resources().iter().collect();
+            if resources.is_empty() {
+                continue;
+            }
+            let manifest_dir = create_manifest_dir(&output_dir, label)?;
+            for (uri, bytes) in resources {
+                let resource = create_resource(&formats, label, uri, bytes);
+                let path = manifest_dir.join(get_file_name(uri, &resource.mime_type));
+                fs::write(&path, &resource.bytes)?;
+                files.push(FileData::new(Some(path), None, None));
+            }
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_directory_names() {
+        assert_eq!(sanitize(".."), "__");
+        assert_eq!(sanitize("."), "_");
+        assert_eq!(sanitize(""), "_");
+        assert_eq!(sanitize("../c2pa.thumbnail"), ".._c2pa.thumbnail");
+        assert_eq!(get_file_name("self#jumbf=c2pa/..", "image/jpeg"), "__.jpg");
+    }
+
+    #[test]
+    fn creates_manifest_dirs_in_output_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path().join("resources");
+        for label in ["..", ".", "", "urn:uuid:1234"] {
+            let manifest_dir = create_manifest_dir(&output_dir, label).unwrap();
+            assert_eq!(manifest_dir.parent(), Some(output_dir.as_path()));
+            assert!(manifest_dir.is_dir());
+        }
+
+        #[cfg(unix)]
+        {
+            let outside_dir = temp_dir.path().join("outside");
+            fs::create_dir(&outside_dir).unwrap();
+            std::os::unix::fs::symlink(&outside_dir, output_dir.join("link")).unwrap();
+            let error = create_manifest_dir(&output_dir, "link").unwrap_err();
+            assert_eq!(error.code(), 700);
+        }
+    }
+}
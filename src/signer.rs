@@ -0,0 +1,188 @@
+use std::fmt::Debug;
+use std::result::Result;
+use std::sync::Arc;
+
+use c2pa::SigningAlg;
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Padding;
+use openssl::sign::{RsaPssSaltlen, Signer};
+use openssl::x509::X509;
+
+use crate::certificates::Certificate;
+use crate::common::SimpleC2PAError;
+
+/// Signs claims with a key held outside of this crate, such as in Android Keystore, the iOS
+/// Secure Enclave or a signing service.
+pub trait ClaimSigner: Send + Sync + Debug {
+    /// Signs `data` with the algorithm returned by [`ClaimSigner::get_algorithm`]. ECDSA
+    /// signatures may be a DER encoded `ECDSA-Sig-Value`, as produced by most platform
+    /// keystores, or the raw concatenation of `r` and `s`, each padded to the size of the
+    /// curve. A signature is read as DER when it is a complete DER `ECDSA-Sig-Value`, and as
+    /// raw otherwise.
+    fn sign(&self, data: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError>;
+
+    fn get_algorithm(&self) -> SigningAlg;
+
+    /// Returns the signing certificate followed by the certificates that issued it, as
+    /// concatenated PEM.
+    fn get_certificate_chain(&self) -> Result<Vec<u8>, SimpleC2PAError>;
+
+    /// Returns the number of bytes to reserve in the asset for the signature.
+    fn get_reserve_size(&self) -> u32;
+}
+
+/// A [`ClaimSigner`] that signs in-process with the private key of a [`Certificate`].
+#[derive(Debug)]
+pub struct LocalSigner {
+    private_key: PKey<Private>,
+    algorithm: SigningAlg,
+    certificate_chain: Vec<u8>,
+}
+
+impl LocalSigner {
+    pub fn new(
+        certificate: Arc<Certificate>,
+        algorithm: Option<SigningAlg>,
+    ) -> Result<Arc<Self>, SimpleC2PAError> {
+        let algorithm = certificate.get_signing_algorithm(algorithm)?;
+        let private_key = PKey::private_key_from_pem(&certificate.get_private_key_bytes()?)
+            .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
+        let certificate_chain = certificate.get_certificate_chain_bytes(false)?;
+
+        Ok(Arc::new(LocalSigner {
+            private_key,
+            algorithm,
+            certificate_chain,
+        }))
+    }
+}
+
+impl ClaimSigner for LocalSigner {
+    fn sign(&self, data: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+        let digest = match self.algorithm {
+            SigningAlg::Es256 | SigningAlg::Ps256 => MessageDigest::sha256(),
+            SigningAlg::Es384 | SigningAlg::Ps384 => MessageDigest::sha384(),
+            SigningAlg::Es512 | SigningAlg::Ps512 => MessageDigest::sha512(),
+            SigningAlg::Ed25519 => {
+                let mut signer = Signer::new_without_digest(&self.private_key)?;
+                return Ok(signer.sign_oneshot_to_vec(&data)?);
+            }
+        };
+
+        let mut signer = Signer::new(digest, &self.private_key)?;
+        if matches!(
+            self.algorithm,
+            SigningAlg::Ps256 | SigningAlg::Ps384 | SigningAlg::Ps512
+        ) {
+            signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+            signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+            signer.set_rsa_mgf1_md(digest)?;
+        }
+        signer.update(&data)?;
+        Ok(signer.sign_to_vec()?)
+    }
+
+    fn get_algorithm(&self) -> SigningAlg {
+        self.algorithm
+    }
+
+    fn get_certificate_chain(&self) -> Result<Vec<u8>, SimpleC2PAError> {
+        Ok(self.certificate_chain.clone())
+    }
+
+    fn get_reserve_size(&self) -> u32 {
+        1024 + self.certificate_chain.len() as u32
+    }
+}
+
+/// Exposes a [`ClaimSigner`] to c2pa.
+#[derive(Debug)]
+pub(crate) struct ClaimSignerAdapter {
+    signer: Arc<dyn ClaimSigner>,
+    algorithm: SigningAlg,
+    certificates: Vec<Vec<u8>>,
+}
+
+impl ClaimSignerAdapter {
+    pub(crate) fn new(signer: Arc<dyn ClaimSigner>) -> Result<Self, SimpleC2PAError> {
+        let certificates = X509::stack_from_pem(&signer.get_certificate_chain()?)
+            .map_err(|source| SimpleC2PAError::CertificateParse { source })?
+            .iter()
+            .map(|certificate| certificate.to_der())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ClaimSignerAdapter {
+            algorithm: signer.get_algorithm(),
+            signer,
+            certificates,
+        })
+    }
+}
+
+/// Length in bytes of each of the `r` and `s` components of an ECDSA signature.
+const fn ecdsa_component_length(algorithm: SigningAlg) -> Option<usize> {
+    match algorithm {
+        SigningAlg::Es256 => Some(32),
+        SigningAlg::Es384 => Some(48),
+        SigningAlg::Es512 => Some(66),
+        _ => None,
+    }
+}
+
+/// Returns the raw `r` and `s` of an ECDSA signature returned by a [`ClaimSigner`], as c2pa
+/// expects. The signature is DER encoded if it parses as an `ECDSA-Sig-Value` that encodes
+/// back to the same bytes, with components that fit in `length` bytes.
+fn ecdsa_signature_to_raw(
+    signature: Vec<u8>,
+    algorithm: SigningAlg,
+    length: usize,
+) -> Result<Vec<u8>, SimpleC2PAError> {
+    let parsed = EcdsaSig::from_der(&signature).ok().filter(|parsed| {
+        parsed.to_der().is_ok_and(|der| der == signature)
+            && parsed.r().num_bytes() as usize <= length
+            && parsed.s().num_bytes() as usize <= length
+    });
+    match parsed {
+        Some(parsed) => {
+            let mut raw = parsed.r().to_vec_padded(length as i32)?;
+            raw.extend(parsed.s().to_vec_padded(length as i32)?);
+            Ok(raw)
+        }
+        None if signature.len() == length * 2 => Ok(signature),
+        None => Err(SimpleC2PAError::InvalidArgument {
+            message: format!(
+                "The {} signature is neither DER encoded nor {} raw bytes",
+                algorithm,
+                length * 2
+            ),
+        }),
+    }
+}
+
+impl c2pa::Signer for ClaimSignerAdapter {
+    fn sign(&self, data: &[u8]) -> c2pa::Result<Vec<u8>> {
+        let signature = self
+            .signer
+            .sign(data.to_vec())
+            .map_err(|error| c2pa::Error::OtherError(Box::new(error)))?;
+        match ecdsa_component_length(self.algorithm) {
+            Some(length) => ecdsa_signature_to_raw(signature, self.algorithm, length)
+                .map_err(|error| c2pa::Error::OtherError(Box::new(error))),
+            None => Ok(signature),
+        }
+    }
+
+    fn alg(&self) -> SigningAlg {
+        self.algorithm
+    }
+
+    fn certs(&self) -> c2pa::Result<Vec<Vec<u8>>> {
+        Ok(self.certificates.clone())
+    }
+
+    fn reserve_size(&self) -> usize {
+        self.signer.get_reserve_size() as usize
+    }
+}
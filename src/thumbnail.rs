@@ -0,0 +1,311 @@
+//! Thumbnails of the signed asset, in the claim, and of its ingredients.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::result::Result;
+use std::sync::Arc;
+
+use c2pa::{Ingredient, Manifest};
+use image::{DynamicImage, ImageOutputFormat};
+use tracing::debug;
+
+use crate::common::{FileData, SimpleC2PAError};
+use crate::content_credentials::ContentCredentials;
+
+/// Longest edge of a generated thumbnail by default, the same as c2pa uses.
+const DEFAULT_MAX_SIZE: u32 = 1024;
+const DEFAULT_QUALITY: u8 = 80;
+/// Formats thumbnails are generated from, those the `image` features decode.
+const DECODABLE_MIME_TYPES: [&str; 4] = ["image/jpeg", "image/png", "image/tiff", "image/webp"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailFormat {
+    Jpeg,
+    Png,
+}
+
+impl ThumbnailFormat {
+    pub const fn get_mime_type(&self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "image/jpeg",
+            ThumbnailFormat::Png => "image/png",
+        }
+    }
+}
+
+/// How a thumbnail is generated from an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailOptions {
+    /// Longest edge of the thumbnail in pixels. Smaller images are not enlarged.
+    pub max_size: u32,
+    /// JPEG quality, from 1 to 100. Ignored for PNG.
+    pub quality: u8,
+    pub format: ThumbnailFormat,
+}
+
+impl ThumbnailOptions {
+    fn validate(&self) -> Result<(), SimpleC2PAError> {
+        if self.max_size == 0 || !(1..=100).contains(&self.quality) {
+            return Err(SimpleC2PAError::InvalidArgument {
+                message: format!("Invalid thumbnail options: {:?}", self),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for ThumbnailOptions {
+    fn default() -> Self {
+        ThumbnailOptions {
+            max_size: DEFAULT_MAX_SIZE,
+            quality: DEFAULT_QUALITY,
+            format: ThumbnailFormat::Jpeg,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Thumbnail {
+    /// No thumbnail. This keeps the manifest small, and keeps a redacted ingredient from
+    /// still being visible in its thumbnail.
+    None,
+    /// A thumbnail generated from the image. Signing fails with
+    /// [`SimpleC2PAError::UnsupportedFormat`] for anything but a JPEG, PNG, TIFF or WebP
+    /// image, such as HEIC, AVIF, video or audio.
+    Generated(ThumbnailOptions),
+    /// A thumbnail supplied by the caller, as a JPEG, PNG or any other image format.
+    Custom(Vec<u8>),
+}
+
+impl Default for Thumbnail {
+    fn default() -> Self {
+        Thumbnail::Generated(ThumbnailOptions::default())
+    }
+}
+
+/// The thumbnails chosen for the claim and the ingredients. Until one is chosen, thumbnails
+/// are generated with [`ThumbnailOptions::default`] for the formats they can be generated
+/// from, and left out for the others.
+#[derive(Debug, Default)]
+pub(crate) struct ThumbnailSettings {
+    pub(crate) claim: Option<Thumbnail>,
+    pub(crate) ingredients: Option<Thumbnail>,
+    /// Thumbnails chosen for single ingredients, by instance ID.
+    pub(crate) by_ingredient: HashMap<String, Thumbnail>,
+}
+
+fn generate(
+    file: &FileData,
+    options: &ThumbnailOptions,
+) -> Result<(String, Vec<u8>), SimpleC2PAError> {
+    let mime_type = file.get_mime_type()?;
+    if !DECODABLE_MIME_TYPES.contains(&mime_type.as_str()) {
+        return Err(SimpleC2PAError::UnsupportedFormat {
+            format: mime_type,
+            supported: DECODABLE_MIME_TYPES.map(str::to_owned).to_vec(),
+        });
+    }
+    let image = image::load_from_memory(&file.get_bytes()?)?;
+
+    let mut thumbnail = if image.width() > options.max_size || image.height() > options.max_size {
+        image.thumbnail(options.max_size, options.max_size)
+    } else {
+        image
+    };
+    let format = match options.format {
+        ThumbnailFormat::Jpeg => {
+            // JPEG has no alpha channel.
+            thumbnail = DynamicImage::ImageRgb8(thumbnail.to_rgb8());
+            ImageOutputFormat::Jpeg(options.quality)
+        }
+        ThumbnailFormat::Png => ImageOutputFormat::Png,
+    };
+    let mut output = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut output, format)?;
+    Ok((
+        options.format.get_mime_type().to_owned(),
+        output.into_inner(),
+    ))
+}
+
+impl Thumbnail {
+    fn validate(&self) -> Result<(), SimpleC2PAError> {
+        match self {
+            Thumbnail::None => Ok(()),
+            Thumbnail::Generated(options) => options.validate(),
+            Thumbnail::Custom(bytes) => {
+                let mime_type = FileData::new(None, Some(bytes.clone()), None).get_mime_type()?;
+                if !mime_type.starts_with("image/") {
+                    return Err(SimpleC2PAError::InvalidArgument {
+                        message: format!("A {} thumbnail is not an image", mime_type),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the MIME type and the bytes of the thumbnail of `file`, if it gets one.
+    fn create(&self, file: &FileData) -> Result<Option<(String, Vec<u8>)>, SimpleC2PAError> {
+        match self {
+            Thumbnail::None => Ok(None),
+            Thumbnail::Generated(options) => generate(file, options).map(Some),
+            Thumbnail::Custom(bytes) => {
+                let mime_type = FileData::new(None, Some(bytes.clone()), None).get_mime_type()?;
+                Ok(Some((mime_type, bytes.clone())))
+            }
+        }
+    }
+}
+
+/// Returns the thumbnail of `file` chosen by `thumbnail`, or else the one generated by
+/// default, if it gets one.
+fn create_thumbnail(
+    thumbnail: Option<&Thumbnail>,
+    file: &FileData,
+) -> Result<Option<(String, Vec<u8>)>, SimpleC2PAError> {
+    if let Some(thumbnail) = thumbnail {
+        return thumbnail.create(file);
+    }
+    match generate(file, &ThumbnailOptions::default()) {
+        Ok(thumbnail) => Ok(Some(thumbnail)),
+        Err(
+            error @ (SimpleC2PAError::UnsupportedFormat { .. } | SimpleC2PAError::Thumbnail { .. }),
+        ) => {
+            debug!("No thumbnail generated: {}", error);
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns a copy of `ingredient` without its thumbnail, which c2pa has no way to remove.
+/// Every other field is carried over, with the resources it refers to.
+fn remove_thumbnail(ingredient: &Ingredient) -> Result<Ingredient, SimpleC2PAError> {
+    let mut copy = Ingredient::new(
+        ingredient.title(),
+        ingredient.format(),
+        ingredient.instance_id(),
+    );
+    copy.set_relationship(ingredient.relationship().clone());
+    if let Some(document_id) = ingredient.document_id() {
+        copy.set_document_id(document_id);
+    }
+    if let Some(provenance) = ingredient.provenance() {
+        copy.set_provenance(provenance);
+    }
+    if let Some(hash) = ingredient.hash() {
+        copy.set_hash(hash);
+    }
+    if let Some(active_manifest) = ingredient.active_manifest() {
+        copy.set_active_manifest(active_manifest);
+    }
+    if let Some(validation_status) = ingredient.validation_status() {
+        copy.set_validation_status(validation_status.to_vec());
+    }
+    if let Some(metadata) = ingredient.metadata() {
+        copy.set_metadata(metadata.clone());
+    }
+    if let Some(description) = ingredient.description() {
+        copy.set_description(description);
+    }
+    if let Some(informational_uri) = ingredient.informational_uri() {
+        copy.set_informational_uri(informational_uri);
+    }
+    for data_type in ingredient.data_types().into_iter().flatten() {
+        copy.add_data_type(data_type.clone());
+    }
+    if let Some(manifest_data) = ingredient.manifest_data() {
+        copy.set_manifest_data(manifest_data.into_owned())?;
+    }
+    if let Some(data_ref) = ingredient.data_ref() {
+        let data = ingredient.resources().get(&data_ref.identifier)?;
+        copy.resources_mut()
+            .add(data_ref.identifier.clone(), data.into_owned())?;
+        copy.set_data_ref(data_ref.clone())?;
+    }
+    Ok(copy)
+}
+
+pub(crate) fn add_thumbnails(
+    manifest: &mut Manifest,
+    file: &FileData,
+    settings: &ThumbnailSettings,
+    ingredient_files: &HashMap<String, Arc<FileData>>,
+) -> Result<(), SimpleC2PAError> {
+    if let Some((mime_type, bytes)) = create_thumbnail(settings.claim.as_ref(), file)? {
+        manifest.set_thumbnail(mime_type, bytes)?;
+    }
+
+    for ingredient in manifest.ingredients_mut() {
+        let instance_id = ingredient.instance_id().to_owned();
+        let thumbnail = settings
+            .by_ingredient
+            .get(&instance_id)
+            .or(settings.ingredients.as_ref());
+        if thumbnail == Some(&Thumbnail::None) {
+            if ingredient.thumbnail_ref().is_some() {
+                *ingredient = remove_thumbnail(ingredient)?;
+            }
+            continue;
+        }
+        let Some(file) = ingredient_files.get(&instance_id) else {
+            continue;
+        };
+        // An ingredient that gets no thumbnail by default, such as a video, keeps the one of its
+        // manifest.
+        if let Some((mime_type, bytes)) = create_thumbnail(thumbnail, file)? {
+            ingredient.set_thumbnail(mime_type, bytes)?;
+        }
+    }
+    Ok(())
+}
+
+impl ContentCredentials {
+    /// Chooses the thumbnail of the signed asset in the claim. By default it is generated with
+    /// [`ThumbnailOptions::default`] when the format allows, and left out otherwise.
+    pub fn set_thumbnail(&self, thumbnail: Thumbnail) -> Result<(), SimpleC2PAError> {
+        thumbnail.validate()?;
+        self.thumbnails.lock().unwrap().claim = Some(thumbnail);
+        Ok(())
+    }
+
+    /// Chooses the thumbnail of every ingredient without one of its own from
+    /// [`ContentCredentials::set_ingredient_thumbnail`]. By default they are generated with
+    /// [`ThumbnailOptions::default`] when the format allows, and ingredients of other formats
+    /// keep the thumbnail of their manifest.
+    pub fn set_ingredient_thumbnails(&self, thumbnail: Thumbnail) -> Result<(), SimpleC2PAError> {
+        thumbnail.validate()?;
+        self.thumbnails.lock().unwrap().ingredients = Some(thumbnail);
+        Ok(())
+    }
+
+    /// Chooses the thumbnail of the ingredient with `instance_id`, as returned by
+    /// [`ContentCredentials::add_ingredient`], or of the parent ingredient when it is `None`.
+    pub fn set_ingredient_thumbnail(
+        &self,
+        instance_id: Option<String>,
+        thumbnail: Thumbnail,
+    ) -> Result<(), SimpleC2PAError> {
+        thumbnail.validate()?;
+        let manifest = self.manifest.lock().unwrap();
+        let ingredient = manifest.ingredients().iter().find(|ingredient| {
+            instance_id
+                .as_ref()
+                .map_or(ingredient.is_parent(), |id| ingredient.instance_id() == id)
+        });
+        let Some(ingredient) = ingredient else {
+            let message = match &instance_id {
+                Some(instance_id) => format!("No ingredient has instance ID {}", instance_id),
+                None => "The asset has no parent ingredient".to_owned(),
+            };
+            return Err(SimpleC2PAError::InvalidArgument { message });
+        };
+        self.thumbnails
+            .lock()
+            .unwrap()
+            .by_ingredient
+            .insert(ingredient.instance_id().to_owned(), thumbnail);
+        Ok(())
+    }
+}
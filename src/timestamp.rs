@@ -0,0 +1,356 @@
+use std::fmt::Debug;
+use std::io::Read;
+use std::result::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+use c2pa::{Signer, SigningAlg};
+use chrono::Utc;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::sha::sha256;
+use openssl::x509::X509;
+
+use crate::certificates::{
+    create_certificate, create_private_key, generate_serial_number, Certificate,
+    CertificateOptions, CertificateType,
+};
+use crate::common::SimpleC2PAError;
+use crate::der;
+use crate::remote_manifest::DEFAULT_TIMEOUT;
+
+/// Space reserved in the asset for the timestamp token.
+const TIMESTAMP_RESERVE_SIZE: usize = 10000;
+/// Largest timestamp response accepted, well above any token that fits the reserved space.
+const DEFAULT_MAX_RESPONSE_SIZE: u64 = 64 * 1024;
+
+const OID_SIGNED_DATA: &[u64] = &[1, 2, 840, 113549, 1, 7, 2];
+const OID_TST_INFO: &[u64] = &[1, 2, 840, 113549, 1, 9, 16, 1, 4];
+const OID_CONTENT_TYPE: &[u64] = &[1, 2, 840, 113549, 1, 9, 3];
+const OID_MESSAGE_DIGEST: &[u64] = &[1, 2, 840, 113549, 1, 9, 4];
+const OID_SIGNING_CERTIFICATE_V2: &[u64] = &[1, 2, 840, 113549, 1, 9, 16, 2, 47];
+const OID_SHA256: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+const OID_ECDSA_WITH_SHA256: &[u64] = &[1, 2, 840, 10045, 4, 3, 2];
+const OID_SHA256_WITH_RSA: &[u64] = &[1, 2, 840, 113549, 1, 1, 11];
+const OID_ANY_POLICY: &[u64] = &[2, 5, 29, 32, 0];
+
+/// Delivers RFC 3161 timestamp requests to a timestamp authority.
+pub trait TimestampTransport: Send + Sync + Debug {
+    /// Sends a DER encoded `TimeStampReq` to `url` and returns the DER encoded `TimeStampResp`.
+    fn send_request(&self, url: String, request: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError>;
+}
+
+/// Sends timestamp requests over HTTP as described in RFC 3161 section 3.4. Requests are
+/// sent while signing, so they time out and responses are limited in size.
+#[derive(Debug)]
+pub struct HttpTimestampTransport {
+    agent: ureq::Agent,
+    max_size: u64,
+}
+
+impl HttpTimestampTransport {
+    pub fn new() -> Arc<Self> {
+        Arc::new(HttpTimestampTransport::default())
+    }
+
+    /// Accepts responses of at most `max_size` bytes, allowing `timeout_seconds` for each
+    /// request, from connecting to reading the whole response.
+    pub fn new_with_limits(timeout_seconds: u64, max_size: u64) -> Arc<Self> {
+        Arc::new(HttpTimestampTransport::with_limits(
+            timeout_seconds,
+            max_size,
+        ))
+    }
+
+    fn with_limits(timeout_seconds: u64, max_size: u64) -> Self {
+        // Connections are not reused: one whose response was cut off at `max_size` still
+        // holds the rest of it, and reading the next response from it never times out.
+        HttpTimestampTransport {
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(timeout_seconds))
+                .max_idle_connections(0)
+                .build(),
+            max_size,
+        }
+    }
+}
+
+impl Default for HttpTimestampTransport {
+    fn default() -> Self {
+        HttpTimestampTransport::with_limits(DEFAULT_TIMEOUT, DEFAULT_MAX_RESPONSE_SIZE)
+    }
+}
+
+impl TimestampTransport for HttpTimestampTransport {
+    fn send_request(&self, url: String, request: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+        let response = self
+            .agent
+            .post(&url)
+            .set("Content-Type", "application/timestamp-query")
+            .send_bytes(&request)
+            .map_err(|error| SimpleC2PAError::Timestamp {
+                message: error.to_string(),
+            })?;
+        let mut body = vec![];
+        response
+            .into_reader()
+            .take(self.max_size + 1)
+            .read_to_end(&mut body)?;
+        if body.len() as u64 > self.max_size {
+            return Err(SimpleC2PAError::Timestamp {
+                message: format!("The response is larger than {} bytes", self.max_size),
+            });
+        }
+        Ok(body)
+    }
+}
+
+#[derive(Debug)]
+pub struct TimestampAuthority {
+    url: String,
+    transport: Arc<dyn TimestampTransport>,
+}
+
+impl TimestampAuthority {
+    /// Requests timestamps from `url`, over HTTP unless another `transport` is given.
+    pub fn new(url: String, transport: Option<Arc<dyn TimestampTransport>>) -> Arc<Self> {
+        Arc::new(TimestampAuthority {
+            url,
+            transport: transport.unwrap_or_else(|| HttpTimestampTransport::new()),
+        })
+    }
+}
+
+/// Adds a timestamp from a [`TimestampAuthority`] to the signatures of another signer.
+pub(crate) struct TimestampingSigner {
+    signer: Box<dyn Signer>,
+    authority: Arc<TimestampAuthority>,
+}
+
+impl TimestampingSigner {
+    pub(crate) fn new(signer: Box<dyn Signer>, authority: Arc<TimestampAuthority>) -> Self {
+        TimestampingSigner { signer, authority }
+    }
+}
+
+impl Signer for TimestampingSigner {
+    fn sign(&self, data: &[u8]) -> c2pa::Result<Vec<u8>> {
+        self.signer.sign(data)
+    }
+
+    fn alg(&self) -> SigningAlg {
+        self.signer.alg()
+    }
+
+    fn certs(&self) -> c2pa::Result<Vec<Vec<u8>>> {
+        self.signer.certs()
+    }
+
+    fn reserve_size(&self) -> usize {
+        self.signer.reserve_size() + TIMESTAMP_RESERVE_SIZE
+    }
+
+    fn ocsp_val(&self) -> Option<Vec<u8>> {
+        self.signer.ocsp_val()
+    }
+
+    fn time_authority_url(&self) -> Option<String> {
+        Some(self.authority.url.clone())
+    }
+
+    fn send_timestamp_request(&self, message: &[u8]) -> Option<c2pa::Result<Vec<u8>>> {
+        let response = self.timestamp_request_body(message).and_then(|request| {
+            self.authority
+                .transport
+                .send_request(self.authority.url.clone(), request)
+                .map_err(|error| c2pa::Error::OtherError(Box::new(error)))
+        });
+        Some(response)
+    }
+}
+
+/// Signs without a timestamp, but reserves the space of one, so that a signature made only to
+/// lay out a manifest store takes the space of the timestamped signature that replaces it.
+pub(crate) struct TimestampReservingSigner {
+    signer: Box<dyn Signer>,
+}
+
+impl TimestampReservingSigner {
+    pub(crate) fn new(signer: Box<dyn Signer>) -> Self {
+        TimestampReservingSigner { signer }
+    }
+}
+
+impl Signer for TimestampReservingSigner {
+    fn sign(&self, data: &[u8]) -> c2pa::Result<Vec<u8>> {
+        self.signer.sign(data)
+    }
+
+    fn alg(&self) -> SigningAlg {
+        self.signer.alg()
+    }
+
+    fn certs(&self) -> c2pa::Result<Vec<Vec<u8>>> {
+        self.signer.certs()
+    }
+
+    fn reserve_size(&self) -> usize {
+        self.signer.reserve_size() + TIMESTAMP_RESERVE_SIZE
+    }
+
+    fn ocsp_val(&self) -> Option<Vec<u8>> {
+        self.signer.ocsp_val()
+    }
+}
+
+/// Returns the `AlgorithmIdentifier` of SHA-256 signatures made with `private_key`.
+fn get_signature_algorithm(private_key: &PKey<Private>) -> Result<Vec<u8>, SimpleC2PAError> {
+    match private_key.id() {
+        Id::EC => Ok(der::algorithm_identifier(OID_ECDSA_WITH_SHA256)),
+        // RSA signature algorithms have NULL rather than absent parameters.
+        Id::RSA => Ok(der::sequence(&[
+            &der::oid(OID_SHA256_WITH_RSA),
+            &der::encode(der::TAG_NULL, &[]),
+        ])),
+        id => Err(SimpleC2PAError::UnsupportedKeyType {
+            key_type: format!("{:?}", id),
+        }),
+    }
+}
+
+/// An in-process timestamp authority, for tests and offline use. Its certificate is issued by
+/// the given root certificate, which verifiers must trust for the timestamps to be trusted.
+#[derive(Debug)]
+pub struct LocalTimestampAuthority {
+    certificate: Arc<Certificate>,
+    private_key: PKey<Private>,
+    signature_algorithm: Vec<u8>,
+}
+
+impl LocalTimestampAuthority {
+    pub fn new(root_certificate: Arc<Certificate>) -> Result<Arc<Self>, SimpleC2PAError> {
+        let options = CertificateOptions::new(
+            create_private_key()?,
+            CertificateType::TimestampAuthority {
+                organization: None,
+                validity_days: None,
+            },
+            Some(root_certificate),
+            None,
+            None,
+        );
+        let certificate = create_certificate(options)?;
+        let private_key = PKey::private_key_from_pem(&certificate.get_private_key_bytes()?)
+            .map_err(|source| SimpleC2PAError::PrivateKeyParse { source })?;
+        let signature_algorithm = get_signature_algorithm(&private_key)?;
+
+        Ok(Arc::new(LocalTimestampAuthority {
+            certificate,
+            private_key,
+            signature_algorithm,
+        }))
+    }
+
+    pub fn get_certificate(&self) -> Arc<Certificate> {
+        self.certificate.clone()
+    }
+
+    fn create_response(&self, request: &[u8]) -> Result<Vec<u8>, SimpleC2PAError> {
+        let (request, _) = der::read_element(request)?;
+        let fields = der::read_elements(request.content)?;
+        let message_imprint = fields
+            .get(1)
+            .filter(|field| field.tag == der::TAG_SEQUENCE)
+            .ok_or_else(|| SimpleC2PAError::Timestamp {
+                message: "Timestamp request has no message imprint".to_owned(),
+            })?;
+        let nonce = fields
+            .iter()
+            .skip(2)
+            .find(|field| field.tag == der::TAG_INTEGER)
+            .map(|field| field.encoded)
+            .unwrap_or_default();
+
+        let serial_number = generate_serial_number()?.to_bn()?.to_vec();
+        let generation_time = Utc::now().format("%Y%m%d%H%M%SZ").to_string();
+        let tst_info = der::sequence(&[
+            &der::unsigned_integer(&[1]),
+            &der::oid(OID_ANY_POLICY),
+            message_imprint.encoded,
+            &der::unsigned_integer(&serial_number),
+            &der::encode(der::TAG_GENERALIZED_TIME, generation_time.as_bytes()),
+            nonce,
+        ]);
+
+        let certificates =
+            X509::stack_from_pem(&self.certificate.get_certificate_chain_bytes(true)?)
+                .map_err(|source| SimpleC2PAError::CertificateParse { source })?;
+        let signing_certificate = &certificates[0];
+        let signing_certificate_der = signing_certificate.to_der()?;
+        let mut certificates_der = vec![];
+        for certificate in &certificates {
+            certificates_der.extend(certificate.to_der()?);
+        }
+
+        let signed_attributes = der::set_of(&[
+            &der::sequence(&[
+                &der::oid(OID_CONTENT_TYPE),
+                &der::set_of(&[&der::oid(OID_TST_INFO)]),
+            ]),
+            &der::sequence(&[
+                &der::oid(OID_MESSAGE_DIGEST),
+                &der::set_of(&[&der::encode(der::TAG_OCTET_STRING, &sha256(&tst_info))]),
+            ]),
+            &der::sequence(&[
+                &der::oid(OID_SIGNING_CERTIFICATE_V2),
+                &der::set_of(&[&der::sequence(&[&der::sequence(&[&der::sequence(&[
+                    &der::encode(der::TAG_OCTET_STRING, &sha256(&signing_certificate_der)),
+                ])])])]),
+            ]),
+        ]);
+        let mut signer = openssl::sign::Signer::new(MessageDigest::sha256(), &self.private_key)?;
+        signer.update(&signed_attributes)?;
+        let signature = signer.sign_to_vec()?;
+
+        let (signed_attributes, _) = der::read_element(&signed_attributes)?;
+        let signer_info = der::sequence(&[
+            &der::unsigned_integer(&[1]),
+            &der::sequence(&[
+                &signing_certificate.issuer_name().to_der()?,
+                &der::unsigned_integer(&signing_certificate.serial_number().to_bn()?.to_vec()),
+            ]),
+            &der::algorithm_identifier(OID_SHA256),
+            &der::encode(der::context_specific(0), signed_attributes.content),
+            &self.signature_algorithm,
+            &der::encode(der::TAG_OCTET_STRING, &signature),
+        ]);
+        let signed_data = der::sequence(&[
+            &der::unsigned_integer(&[3]),
+            &der::set_of(&[&der::algorithm_identifier(OID_SHA256)]),
+            &der::sequence(&[
+                &der::oid(OID_TST_INFO),
+                &der::encode(
+                    der::context_specific(0),
+                    &der::encode(der::TAG_OCTET_STRING, &tst_info),
+                ),
+            ]),
+            &der::encode(der::context_specific(0), &certificates_der),
+            &der::set_of(&[&signer_info]),
+        ]);
+        let time_stamp_token = der::sequence(&[
+            &der::oid(OID_SIGNED_DATA),
+            &der::encode(der::context_specific(0), &signed_data),
+        ]);
+
+        Ok(der::sequence(&[
+            &der::sequence(&[&der::unsigned_integer(&[0])]),
+            &time_stamp_token,
+        ]))
+    }
+}
+
+impl TimestampTransport for LocalTimestampAuthority {
+    fn send_request(&self, _url: String, request: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+        self.create_response(&request)
+    }
+}
@@ -1,10 +1,119 @@
 #[cfg(test)]
 pub mod tests {
+    use openssl::asn1::{Asn1Integer, Asn1Time};
+    use openssl::bn::BigNum;
+    use openssl::ecdsa::EcdsaSig;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::x509::{X509Req, X509};
     use simple_c2pa::{
-        create_content_credentials_certificate, create_root_certificate, ApplicationInfo,
-        ContentCredentials, ExifData, FileData,
+        create_certificate, create_content_credentials_certificate, create_private_key,
+        create_root_certificate, enroll_certificate, AIDataMiningUsage, ActionData, ActionRegion,
+        ActionType, AltitudeRef, Angle, ApplicationInfo, AssertionInfo, AuthorIdentity,
+        Certificate, CertificateAuthority, CertificateOptions, CertificateType, ClaimSigner,
+        ContentCredentials, CustomAITrainingOptions, DigitalSourceType, DirectionRef, ExifData,
+        ExifField, FileData, FragmentedRecording, HttpTimestampTransport, Latitude, LatitudeRef,
+        LocalSigner, LocalTimestampAuthority, LocationPrivacy, Longitude, LongitudeRef,
+        ManifestFetcher, ManifestReader, Rational, Relationship, SignerPayload, SigningAlg,
+        SimpleC2PAError, SpeedRef, Thumbnail, ThumbnailFormat, ThumbnailOptions,
+        TimestampAuthority, TimestampTransport, X509CredentialHolder, LOCATION_LABEL,
+        X509_SIGNATURE_TYPE,
     };
+    use std::borrow::Cow;
+    use std::collections::HashMap;
     use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    /// Returns a root certificate and a content credentials certificate issued by it.
+    fn create_test_certificates() -> (Arc<Certificate>, Arc<Certificate>) {
+        let root_certificate = create_root_certificate(None, None).unwrap();
+        let content_credentials_certificate =
+            create_content_credentials_certificate(Some(root_certificate.clone()), None, None)
+                .unwrap();
+        (root_certificate, content_credentials_certificate)
+    }
+
+    #[derive(Debug)]
+    struct TestCertificateAuthority {
+        root_certificate: Arc<Certificate>,
+    }
+
+    impl CertificateAuthority for TestCertificateAuthority {
+        fn sign_request(&self, request: String) -> Result<String, SimpleC2PAError> {
+            let root_pem = self.root_certificate.get_certificate_bytes()?;
+            let root = X509::from_pem(&root_pem)?;
+            let root_key =
+                PKey::private_key_from_pem(&self.root_certificate.get_private_key_bytes()?)?;
+            let request = X509Req::from_pem(request.as_bytes())?;
+
+            let mut builder = X509::builder()?;
+            builder.set_version(2)?;
+            builder.set_subject_name(request.subject_name())?;
+            builder.set_issuer_name(root.subject_name())?;
+            let public_key = request.public_key()?;
+            let serial_number = Asn1Integer::from_bn(BigNum::from_u32(2)?.as_ref())?;
+            let not_before = Asn1Time::days_from_now(0)?;
+            let not_after = Asn1Time::days_from_now(30)?;
+            builder.set_pubkey(&public_key)?;
+            builder.set_serial_number(&serial_number)?;
+            builder.set_not_before(&not_before)?;
+            builder.set_not_after(&not_after)?;
+            for extension in request.extensions()? {
+                builder.append_extension(extension)?;
+            }
+            builder.sign(&root_key, MessageDigest::sha512())?;
+
+            let mut chain = builder.build().to_pem()?;
+            chain.extend(root_pem);
+            Ok(String::from_utf8(chain).unwrap())
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct TestManifestFetcher {
+        manifests: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    impl ManifestFetcher for TestManifestFetcher {
+        fn fetch(&self, url: String) -> Result<Vec<u8>, SimpleC2PAError> {
+            self.manifests.lock().unwrap().get(&url).cloned().ok_or(
+                SimpleC2PAError::RemoteManifest {
+                    url,
+                    message: "Not found".to_string(),
+                },
+            )
+        }
+    }
+
+    /// Returns ECDSA signatures as the raw `r` and `s`, as some hardware keystores do.
+    #[derive(Debug)]
+    struct RawSigner {
+        signer: Arc<LocalSigner>,
+    }
+
+    impl ClaimSigner for RawSigner {
+        fn sign(&self, data: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+            let signature = EcdsaSig::from_der(&self.signer.sign(data)?)?;
+            let mut raw = signature.r().to_vec_padded(32)?;
+            raw.extend(signature.s().to_vec_padded(32)?);
+            Ok(raw)
+        }
+
+        fn get_algorithm(&self) -> SigningAlg {
+            self.signer.get_algorithm()
+        }
+
+        fn get_certificate_chain(&self) -> Result<Vec<u8>, SimpleC2PAError> {
+            self.signer.get_certificate_chain()
+        }
+
+        fn get_reserve_size(&self) -> u32 {
+            self.signer.get_reserve_size()
+        }
+    }
 
     #[test]
     fn basic_example() {
@@ -12,16 +121,14 @@ pub mod tests {
         let file_name = image_path.split("/").last().unwrap().to_string();
         let file_data = fs::read(image_path).expect("Can't read image");
 
-        let root_certificate = create_root_certificate(None, None).unwrap();
-        let content_credentials_certificate =
-            create_content_credentials_certificate(Some(root_certificate.clone()), None, None)
-                .unwrap();
+        let (_, content_credentials_certificate) = create_test_certificates();
 
         let file = FileData::new(None, Some(file_data), Some(file_name.clone()));
-        let cc = ContentCredentials::new(content_credentials_certificate, file, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
         cc.add_created_assertion().unwrap();
-        let output_path = format!("outputs/c2pa-basic-{}", file_name);
-        let file_data = cc.embed_manifest(Some(output_path.clone())).unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join(format!("c2pa-basic-{}", file_name));
+        let file_data = cc.embed_manifest(None).unwrap();
         fs::write(output_path, file_data.get_bytes().unwrap()).expect("Can't write file");
     }
 
@@ -33,50 +140,62 @@ pub mod tests {
         let fingerprint = "BA08 71E8 0200 B95D 8297  7ED0 4D1E C37F 88A7 FDCE".to_string();
 
         let organization = "Sample Organization".to_string();
-        let root_certificate = create_root_certificate(Some(organization.clone()), None).unwrap();
+        let root_certificate = create_root_certificate(Some(&organization), None).unwrap();
         let root_bytes = root_certificate.get_certificate_bytes().unwrap();
-        let root_path = "outputs/c2pa-root-certificate.crt";
+        let output_dir = tempfile::tempdir().unwrap();
+        let root_path = output_dir.path().join("c2pa-root-certificate.crt");
         fs::write(root_path, root_bytes).expect("Can't write file");
 
         let content_credentials_certificate = create_content_credentials_certificate(
             Some(root_certificate.clone()),
-            Some(organization.clone()),
+            Some(&organization),
             None,
         )
         .unwrap();
         let content_bytes = content_credentials_certificate
             .get_certificate_bytes()
             .unwrap();
-        let content_path = "outputs/c2pa-credentials-certificate.crt";
+        let content_path = output_dir.path().join("c2pa-credentials-certificate.crt");
         fs::write(content_path, content_bytes).expect("Can't write file");
 
         let file = FileData::new(None, Some(file_data), Some(file_name.clone()));
         let app_info = ApplicationInfo::new("SampleApp".to_string(), "1.0.0".to_string(), None);
-        let cc = ContentCredentials::new(content_credentials_certificate, file, Some(app_info));
+        let cc =
+            ContentCredentials::new(content_credentials_certificate, file, Some(app_info)).unwrap();
         cc.add_created_assertion().unwrap();
 
         let exif_data = ExifData {
-            gps_version_id: Some("2.2.0.0".to_string()),
-            latitude: Some("39,21.102N".to_string()),
-            longitude: Some("74,26.5737W".to_string()),
-            altitude_ref: Some(0),
-            altitude: Some("100963/29890".to_string()),
-            timestamp: Some("2019-09-22T18:22:57Z".to_string()),
-            speed_ref: Some("K".to_string()),
-            speed: Some("4009/161323".to_string()),
-            direction_ref: Some("T".to_string()),
-            direction: Some("296140/911".to_string()),
-            destination_bearing_ref: Some("T".to_string()),
-            destination_bearing: Some("296140/911".to_string()),
-            positioning_error: Some("13244/2207".to_string()),
-            exposure_time: Some("1/100".to_string()),
-            f_number: Some(4.0),
+            gps_version_id: Some([2, 2, 0, 0]),
+            latitude: Some(Latitude {
+                angle: Angle::Decimal { degrees: 39.3517 },
+                reference: LatitudeRef::North,
+            }),
+            longitude: Some(Longitude {
+                angle: Angle::DegreesMinutesSeconds {
+                    degrees: 74,
+                    minutes: 26,
+                    seconds: 34.422,
+                },
+                reference: LongitudeRef::West,
+            }),
+            altitude_ref: Some(AltitudeRef::AboveSeaLevel),
+            altitude: Some(Rational::new(100963, 29890)),
+            timestamp: Some("2019-09-22T18:22:57Z".parse().unwrap()),
+            speed_ref: Some(SpeedRef::KilometersPerHour),
+            speed: Some(Rational::new(4009, 161323)),
+            direction_ref: Some(DirectionRef::TrueNorth),
+            direction: Some(Rational::new(296140, 911)),
+            destination_bearing_ref: Some(DirectionRef::TrueNorth),
+            destination_bearing: Some(Rational::new(296140, 911)),
+            positioning_error: Some(Rational::new(13244, 2207)),
+            exposure_time: Some(Rational::new(1, 100)),
+            f_number: Some(Rational::new(4, 1)),
             color_space: Some(1),
             digital_zoom_ratio: Some(2.0),
-            make: Some("ProofMode".to_string()),
-            model: Some("ProofMode In-App Camera v2.0".to_string()),
-            lens_make: Some("CameraCompany".to_string()),
-            lens_model: Some("17.0-35.0 mm".to_string()),
+            make: Some(Cow::Borrowed("ProofMode")),
+            model: Some(Cow::Borrowed("ProofMode In-App Camera v2.0")),
+            lens_make: Some(Cow::Borrowed("CameraCompany")),
+            lens_model: Some(Cow::Borrowed("17.0-35.0 mm")),
             lens_specification: Some(vec![1.55, 4.2, 1.6, 2.4]),
         };
         cc.add_exif_assertion(exif_data).unwrap();
@@ -86,8 +205,1521 @@ pub mod tests {
             .unwrap();
         cc.add_website_assertion("https://redaranj.com".to_string())
             .unwrap();
-        let output_path = format!("outputs/c2pa-complex-{}", file_name);
-        let file_data = cc.embed_manifest(Some(output_path.clone())).unwrap();
+        let output_path = output_dir
+            .path()
+            .join(format!("c2pa-complex-{}", file_name));
+        let file_data = cc.embed_manifest(None).unwrap();
         fs::write(output_path, file_data.get_bytes().unwrap()).expect("Can't write file");
     }
+
+    #[test]
+    fn read_example() {
+        let image_path = "tests/media/test-1.jpg";
+        let file_name = image_path.split("/").last().unwrap().to_string();
+        let file_data = fs::read(image_path).expect("Can't read image");
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(None, Some(file_data), Some(file_name.clone()));
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join(format!("c2pa-read-{}", file_name));
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        assert!(active_manifest.claim_generator.starts_with("Simple-C2PA/"));
+        assert!(active_manifest.signer.is_some());
+        assert!(active_manifest
+            .assertions
+            .iter()
+            .any(|assertion| assertion.label == "c2pa.actions"));
+    }
+
+    #[test]
+    fn memory_example() {
+        let image_path = "tests/media/test-1.jpg";
+        let file_name = image_path.split("/").last().unwrap().to_string();
+        let file_data = fs::read(image_path).expect("Can't read image");
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(None, Some(file_data.clone()), Some(file_name));
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        assert!(signed_file.get_path().is_none());
+        assert_ne!(signed_file.get_bytes().unwrap(), file_data);
+
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.active_manifest.is_some());
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+    }
+
+    #[test]
+    fn sidecar_example() {
+        let image_path = "tests/media/test-1.jpg";
+        let file_name = image_path.split("/").last().unwrap().to_string();
+        let file_data = fs::read(image_path).expect("Can't read image");
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(None, Some(file_data), Some(file_name.clone()));
+        let cc =
+            ContentCredentials::new(content_credentials_certificate, file.clone(), None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir
+            .path()
+            .join(format!("c2pa-sidecar-{}", file_name));
+        let sidecar = cc.export_manifest(Some(output_path)).unwrap();
+        assert_eq!(
+            sidecar.get_path(),
+            Some(output_dir.path().join("c2pa-sidecar-test-1.c2pa"))
+        );
+
+        let report = ContentCredentials::read_with_sidecar(file, sidecar).unwrap();
+        assert!(report.active_manifest.is_some());
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+    }
+
+    #[test]
+    fn remote_manifest_example() {
+        let image_path = "tests/media/test-1.jpg";
+        let file_name = image_path.split("/").last().unwrap().to_string();
+        let file_data = fs::read(image_path).expect("Can't read image");
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(None, Some(file_data), Some(file_name.clone()));
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let url = "https://example.com/manifests/test-1.c2pa".to_string();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join(format!("c2pa-remote-{}", file_name));
+        let remote = cc
+            .embed_remote_manifest(url.clone(), Some(output_path))
+            .unwrap();
+
+        // Without a fetcher the manifest is not fetched from the URL given by the asset.
+        let error = ManifestReader::new(remote.asset.clone()).unwrap_err();
+        assert_eq!(error.code(), 502);
+        assert!(error.to_string().contains(&url));
+
+        let fetcher = Arc::new(TestManifestFetcher::default());
+        let reader = ManifestReader::new_with_fetcher(remote.asset.clone(), fetcher.clone());
+        assert_eq!(reader.unwrap_err().code(), 502);
+
+        fetcher
+            .manifests
+            .lock()
+            .unwrap()
+            .insert(url, remote.manifest_store.get_bytes().unwrap());
+        let reader = ManifestReader::new_with_fetcher(remote.asset, fetcher).unwrap();
+        let report = reader.get_report().unwrap();
+        assert!(report.active_manifest.is_some());
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+    }
+
+    #[test]
+    fn ingredient_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let original = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate.clone(), original, None)
+            .unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_original = cc.embed_manifest(None).unwrap();
+
+        let edited = FileData::new(Some(PathBuf::from("tests/media/test-1.png")), None, None);
+        let sticker = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, edited, None).unwrap();
+        cc.add_ingredient(signed_original, Relationship::ParentOf)
+            .unwrap();
+        cc.add_ingredient(sticker, Relationship::ComponentOf)
+            .unwrap();
+        cc.add_placed_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-ingredient-test-1.png");
+        let signed_edit = cc.embed_manifest(Some(output_path)).unwrap();
+
+        let report = ContentCredentials::read(signed_edit).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        assert_eq!(report.manifests.len(), 2);
+    }
+
+    #[test]
+    fn actions_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let original = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let edited = FileData::new(Some(PathBuf::from("tests/media/test-1.png")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, edited, None).unwrap();
+        let instance_id = cc.add_ingredient(original, Relationship::ParentOf).unwrap();
+
+        let mut opened = ActionData::new(ActionType::Opened);
+        opened.ingredient_instance_id = Some(instance_id);
+        opened.digital_source_type = Some(DigitalSourceType::DigitalCapture);
+        cc.add_action(opened).unwrap();
+        let mut cropped = ActionData::new(ActionType::Cropped);
+        cropped.when = Some(chrono::Utc::now());
+        cropped.software_agent = Some("SampleApp 1.0.0".to_string());
+        cropped
+            .parameters
+            .insert("description".to_string(), "Square crop".into());
+        cropped.changes.push(ActionRegion::Rectangle {
+            x: 10.0,
+            y: 20.0,
+            width: 300.0,
+            height: 300.0,
+        });
+        cc.add_action(cropped).unwrap();
+        cc.add_action(ActionData::new(ActionType::Other {
+            label: String::new(),
+        }))
+        .unwrap_err();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-actions-test-1.png");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let actions: Vec<_> = active_manifest
+            .assertions
+            .iter()
+            .filter(|assertion| assertion.label == "c2pa.actions")
+            .collect();
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].json.contains("c2pa.cropped"));
+        assert!(actions[0]
+            .json
+            .contains("http://cv.iptc.org/newscodes/digitalsourcetype/digitalCapture"));
+    }
+
+    #[test]
+    fn error_example() {
+        let image_path = PathBuf::from("tests/media/test-1.jpg");
+        let file = FileData::new(Some(image_path), None, None);
+        let error = ContentCredentials::read(file.clone()).unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::ManifestNotFound));
+        assert_eq!(error.code(), 500);
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        let error = cc
+            .add_json_assertion("org.example.test", "{not json".to_string())
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            SimpleC2PAError::InvalidAssertionJson { .. }
+        ));
+    }
+
+    #[test]
+    fn signing_algorithm_example() {
+        let (root_certificate, content_credentials_certificate) = create_test_certificates();
+        assert_eq!(
+            content_credentials_certificate
+                .get_signing_algorithm(None)
+                .unwrap(),
+            SigningAlg::Es256
+        );
+        let error = content_credentials_certificate
+            .get_signing_algorithm(Some(SigningAlg::Es384))
+            .unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::InvalidArgument { .. }));
+
+        let certificate_data = FileData::new(
+            None,
+            Some(
+                content_credentials_certificate
+                    .get_certificate_bytes()
+                    .unwrap(),
+            ),
+            None,
+        );
+        let mismatched_certificate = Certificate::new(
+            certificate_data,
+            create_private_key().unwrap(),
+            Some(root_certificate),
+        );
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(mismatched_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-mismatched-test-1.jpg");
+        let error = cc.embed_manifest(Some(output_path)).unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::KeyMismatch));
+    }
+
+    #[test]
+    fn certificate_chain_example() {
+        let root_certificate = create_root_certificate(None, None).unwrap();
+        let intermediate_options = CertificateOptions::new(
+            create_private_key().unwrap(),
+            CertificateType::OnlineIntermediate {
+                organization: None,
+                validity_days: None,
+            },
+            Some(root_certificate),
+            None,
+            None,
+        );
+        let intermediate_certificate = create_certificate(intermediate_options).unwrap();
+        let content_credentials_certificate =
+            create_content_credentials_certificate(Some(intermediate_certificate), None, None)
+                .unwrap();
+
+        let count_certificates = |chain: Vec<u8>| {
+            String::from_utf8(chain)
+                .unwrap()
+                .matches("-----BEGIN CERTIFICATE-----")
+                .count()
+        };
+        let chain = content_credentials_certificate
+            .get_certificate_chain_bytes(false)
+            .unwrap();
+        assert_eq!(count_certificates(chain), 2);
+        let chain = content_credentials_certificate
+            .get_certificate_chain_bytes(true)
+            .unwrap();
+        assert_eq!(count_certificates(chain), 3);
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-chain-test-1.jpg");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+    }
+
+    #[test]
+    fn certificate_request_example() {
+        let root_certificate = create_root_certificate(None, None).unwrap();
+        let authority = Arc::new(TestCertificateAuthority { root_certificate });
+        let options = CertificateOptions::new(
+            create_private_key().unwrap(),
+            CertificateType::ContentCredentials {
+                organization: Some("Sample Organization"),
+                validity_days: None,
+            },
+            None,
+            Some("jane@example.com"),
+            None,
+        );
+        let content_credentials_certificate = enroll_certificate(options, authority).unwrap();
+        let chain = content_credentials_certificate
+            .get_certificate_chain_bytes(true)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(chain)
+                .unwrap()
+                .matches("-----BEGIN CERTIFICATE-----")
+                .count(),
+            2
+        );
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-enrolled-test-1.jpg");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+    }
+
+    #[test]
+    fn email_example() {
+        let root_certificate = create_root_certificate(None, None).unwrap();
+        let options = CertificateOptions::new(
+            create_private_key().unwrap(),
+            CertificateType::ContentCredentials {
+                organization: None,
+                validity_days: None,
+            },
+            Some(root_certificate),
+            Some("jane@example.com"),
+            None,
+        );
+        let content_credentials_certificate = create_certificate(options).unwrap();
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        let error = cc
+            .add_email_assertion("john@example.com".to_string(), "John Doe".to_string(), true)
+            .unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::EmailMismatch { .. }));
+        assert_eq!(error.code(), 205);
+        cc.add_email_assertion("Jane@example.com".to_string(), "Jane Doe".to_string(), true)
+            .unwrap();
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-email-test-1.jpg");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        assert!(active_manifest.assertions.iter().any(|assertion| {
+            assertion.label == "stds.schema-org.CreativeWork"
+                && assertion.json.contains("Jane@example.com")
+        }));
+    }
+
+    #[test]
+    fn author_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_author(
+            "Jane Doe".to_string(),
+            vec![
+                AuthorIdentity::Mastodon {
+                    handle: "@jane@example.social".to_string(),
+                },
+                AuthorIdentity::GitHub {
+                    username: "janedoe".to_string(),
+                },
+                AuthorIdentity::Orcid {
+                    id: "0000-0002-1825-0097".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        cc.add_instagram_assertion("johndoe".to_string(), "John Doe".to_string())
+            .unwrap();
+        cc.add_website_assertion("https://example.com".to_string())
+            .unwrap();
+        cc.add_website_assertion("https://example.org".to_string())
+            .unwrap();
+        let error = cc
+            .add_author(
+                "Jane Doe".to_string(),
+                vec![AuthorIdentity::Mastodon {
+                    handle: "jane".to_string(),
+                }],
+            )
+            .unwrap_err();
+        assert_eq!(error.code(), 700);
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-author-test-1.jpg");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let works: Vec<_> = active_manifest
+            .assertions
+            .iter()
+            .filter(|assertion| assertion.label == "stds.schema-org.CreativeWork")
+            .collect();
+        assert_eq!(works.len(), 1);
+        assert!(works[0].json.contains("https://example.social/@jane"));
+        assert!(works[0].json.contains("https://github.com/janedoe"));
+        assert!(works[0].json.contains("https://instagram.com/johndoe"));
+        assert!(works[0].json.contains("https://example.org"));
+    }
+
+    #[test]
+    fn claim_signer_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let signer = LocalSigner::new(content_credentials_certificate, None).unwrap();
+        assert_eq!(signer.get_algorithm(), SigningAlg::Es256);
+        let raw_signer = Arc::new(RawSigner {
+            signer: signer.clone(),
+        });
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let signers: Vec<Arc<dyn ClaimSigner>> = vec![signer, raw_signer];
+        for (index, signer) in signers.into_iter().enumerate() {
+            let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+            let cc = ContentCredentials::new_with_signer(signer, file, None).unwrap();
+            cc.add_created_assertion().unwrap();
+            let output_path = output_dir
+                .path()
+                .join(format!("c2pa-signer-{}-test-1.jpg", index));
+            let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+            let report = ContentCredentials::read(signed_file).unwrap();
+            assert!(report.is_valid, "{:?}", report.validation_statuses);
+        }
+    }
+
+    #[test]
+    fn timestamp_example() {
+        let (root_certificate, content_credentials_certificate) = create_test_certificates();
+        let local_authority = LocalTimestampAuthority::new(root_certificate).unwrap();
+        let authority =
+            TimestampAuthority::new("http://localhost/tsa".to_string(), Some(local_authority));
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.set_timestamp_authority(Some(authority));
+        cc.add_created_assertion().unwrap();
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-timestamp-test-1.jpg");
+        // The timestamp authority gives the time in whole seconds.
+        let started = chrono::Utc::now().timestamp();
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let finished = chrono::Utc::now().timestamp();
+
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        assert!(report
+            .validation_statuses
+            .iter()
+            .all(|status| !status.code.starts_with("timeStamp.")));
+        // c2pa only reads the signing time from a timestamp that matches the signature.
+        let signer = report.active_manifest.unwrap().signer.unwrap();
+        let signing_time =
+            chrono::DateTime::parse_from_rfc3339(&signer.signing_time.expect("No timestamp"))
+                .unwrap()
+                .timestamp();
+        assert!((started..=finished).contains(&signing_time));
+    }
+
+    #[test]
+    fn timestamp_transport_limits_example() {
+        // A server that answers the first request with a response that is too large, and
+        // never answers the second.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/tsa", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            assert!(stream.read(&mut [0; 4096]).unwrap() > 0);
+            let body = vec![0u8; 4096];
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/timestamp-reply\r\n\
+                 Content-Length: {}\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            let _ = stream.write_all(&body);
+            let (stream, _) = listener.accept().unwrap();
+            std::thread::sleep(std::time::Duration::from_secs(3));
+            drop(stream);
+        });
+
+        let transport = HttpTimestampTransport::new_with_limits(1, 1024);
+        let error = transport.send_request(url.clone(), vec![]).unwrap_err();
+        assert_eq!(error.code(), 401);
+        assert!(error.to_string().contains("1024"));
+        let started = std::time::Instant::now();
+        let error = transport.send_request(url, vec![]).unwrap_err();
+        assert_eq!(error.code(), 401);
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        server.join().unwrap();
+    }
+
+    /// The type and payload of a JUMBF box.
+    type TestBox<'a> = (&'a [u8], &'a [u8]);
+
+    /// Returns the boxes in `input`.
+    fn read_test_boxes(mut input: &[u8]) -> Vec<TestBox<'_>> {
+        let mut boxes = vec![];
+        while !input.is_empty() {
+            let length = u32::from_be_bytes(input[..4].try_into().unwrap()) as usize;
+            boxes.push((&input[4..8], &input[8..length]));
+            input = &input[length..];
+        }
+        boxes
+    }
+
+    /// Returns the label and the boxes of a JUMBF superbox, after its description box.
+    fn read_test_superbox(payload: &[u8]) -> (String, Vec<TestBox<'_>>) {
+        let mut boxes = read_test_boxes(payload);
+        let (box_type, description) = boxes.remove(0);
+        assert_eq!(box_type, b"jumd");
+        let label = description[17..].split(|byte| *byte == 0).next().unwrap();
+        (String::from_utf8(label.to_vec()).unwrap(), boxes)
+    }
+
+    /// Returns the CBOR content of the box labelled with `label` among `boxes`.
+    fn find_test_cbor(boxes: &[TestBox<'_>], label: &str) -> serde_cbor::Value {
+        let content = boxes
+            .iter()
+            .filter(|(box_type, _)| *box_type == b"jumb")
+            .map(|(_, payload)| read_test_superbox(payload))
+            .find(|(box_label, _)| box_label.starts_with(label))
+            .and_then(|(_, boxes)| boxes.into_iter().find(|(box_type, _)| *box_type == b"cbor"))
+            .unwrap_or_else(|| panic!("No {} box", label))
+            .1;
+        serde_cbor::from_slice(content).unwrap()
+    }
+
+    fn get_test_field<'a>(value: &'a serde_cbor::Value, key: &str) -> &'a serde_cbor::Value {
+        let serde_cbor::Value::Map(fields) = value else {
+            panic!("Not a CBOR map: {:?}", value);
+        };
+        fields
+            .get(&serde_cbor::Value::Text(key.to_owned()))
+            .unwrap_or_else(|| panic!("No {} field", key))
+    }
+
+    fn get_test_bytes(value: &serde_cbor::Value) -> &[u8] {
+        let serde_cbor::Value::Bytes(bytes) = value else {
+            panic!("Not a CBOR byte string: {:?}", value);
+        };
+        bytes
+    }
+
+    /// Returns the certificate of an identity credential issued by `root_certificate`, and a
+    /// holder of it.
+    fn create_identity_holder(
+        root_certificate: Arc<Certificate>,
+    ) -> (Arc<Certificate>, Arc<X509CredentialHolder>) {
+        let options = CertificateOptions::new(
+            create_private_key().unwrap(),
+            CertificateType::ContentCredentials {
+                organization: None,
+                validity_days: None,
+            },
+            Some(root_certificate),
+            Some("jane@example.com"),
+            None,
+        );
+        let identity_certificate = create_certificate(options).unwrap();
+        let identity_signer = LocalSigner::new(identity_certificate.clone(), None).unwrap();
+        let holder = X509CredentialHolder::new(identity_signer).unwrap();
+        (identity_certificate, holder)
+    }
+
+    #[test]
+    fn identity_example() {
+        let (root_certificate, content_credentials_certificate) = create_test_certificates();
+        let (identity_certificate, holder) = create_identity_holder(root_certificate);
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate, file.clone(), None).unwrap();
+        cc.set_identity_credential_holder(Some(holder));
+        cc.add_created_assertion().unwrap();
+        let sidecar = cc.export_manifest(None).unwrap();
+        let report = ContentCredentials::read_with_sidecar(file, sidecar.clone()).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+
+        // The active manifest is the last one in the store.
+        let manifest_store = sidecar.get_bytes().unwrap();
+        let (_, store) = read_test_boxes(&manifest_store)[0];
+        let (_, manifests) = read_test_superbox(store);
+        let (_, manifest) = *manifests.last().unwrap();
+        let (_, manifest_boxes) = read_test_superbox(manifest);
+        let claim = find_test_cbor(&manifest_boxes, "c2pa.claim");
+        let (_, assertion_store) = manifest_boxes
+            .iter()
+            .map(|(_, payload)| read_test_superbox(payload))
+            .find(|(label, _)| label == "c2pa.assertions")
+            .unwrap();
+        let identity = find_test_cbor(&assertion_store, "cawg.identity");
+
+        // The signature and the padding are stored as byte strings.
+        get_test_bytes(get_test_field(&identity, "pad1"));
+        let signer_payload: SignerPayload =
+            serde_cbor::value::from_value(get_test_field(&identity, "signer_payload").clone())
+                .unwrap();
+        assert_eq!(signer_payload.sig_type, X509_SIGNATURE_TYPE);
+
+        // The signature is a COSE_Sign1 by the holder over the signer payload.
+        let serde_cbor::Value::Tag(18, cose_sign1) =
+            serde_cbor::from_slice(get_test_bytes(get_test_field(&identity, "signature"))).unwrap()
+        else {
+            panic!("The signature is not a COSE_Sign1");
+        };
+        let serde_cbor::Value::Array(cose_sign1) = *cose_sign1 else {
+            panic!("The COSE_Sign1 is not an array");
+        };
+        let protected_header = get_test_bytes(&cose_sign1[0]);
+        let serde_cbor::Value::Map(headers) = serde_cbor::from_slice(protected_header).unwrap()
+        else {
+            panic!("The protected header is not a map");
+        };
+        let holder_certificate =
+            X509::from_pem(&identity_certificate.get_certificate_bytes().unwrap()).unwrap();
+        let x5chain = match &headers[&serde_cbor::Value::Integer(33)] {
+            serde_cbor::Value::Array(certificates) => get_test_bytes(&certificates[0]),
+            certificate => get_test_bytes(certificate),
+        };
+        assert_eq!(x5chain, holder_certificate.to_der().unwrap());
+
+        let to_be_signed = serde_cbor::to_vec(&serde_cbor::Value::Array(vec![
+            serde_cbor::Value::Text("Signature1".to_owned()),
+            serde_cbor::Value::Bytes(protected_header.to_vec()),
+            serde_cbor::Value::Bytes(vec![]),
+            serde_cbor::Value::Bytes(serde_cbor::to_vec(&signer_payload).unwrap()),
+        ]))
+        .unwrap();
+        let digest = openssl::sha::sha256(&to_be_signed);
+        let signature = get_test_bytes(&cose_sign1[3]);
+        let (r, s) = signature.split_at(signature.len() / 2);
+        let signature = EcdsaSig::from_private_components(
+            BigNum::from_slice(r).unwrap(),
+            BigNum::from_slice(s).unwrap(),
+        )
+        .unwrap();
+        let public_key = holder_certificate.public_key().unwrap().ec_key().unwrap();
+        assert!(signature.verify(&digest, &public_key).unwrap());
+
+        // Every referenced assertion has the hash it has in the final claim.
+        let serde_cbor::Value::Array(claim_assertions) = get_test_field(&claim, "assertions")
+        else {
+            panic!("The claim has no assertions");
+        };
+        let claim_hashes: HashMap<String, &[u8]> = claim_assertions
+            .iter()
+            .map(|assertion| {
+                let serde_cbor::Value::Text(url) = get_test_field(assertion, "url") else {
+                    panic!("The assertion URL is not text");
+                };
+                let label = url.rsplit('/').next().unwrap().to_owned();
+                (label, get_test_bytes(get_test_field(assertion, "hash")))
+            })
+            .collect();
+        let mut labels = vec![];
+        for reference in &signer_payload.referenced_assertions {
+            let label = reference.url.rsplit('/').next().unwrap();
+            assert_eq!(claim_hashes[label], reference.hash, "{}", label);
+            labels.push(label);
+        }
+        assert!(labels.contains(&"c2pa.hash.data"));
+        assert!(labels.contains(&"c2pa.actions"));
+    }
+
+    #[derive(Debug)]
+    struct CountingTransport {
+        transport: Arc<dyn TimestampTransport>,
+        requests: Mutex<usize>,
+    }
+
+    impl TimestampTransport for CountingTransport {
+        fn send_request(&self, url: String, request: Vec<u8>) -> Result<Vec<u8>, SimpleC2PAError> {
+            *self.requests.lock().unwrap() += 1;
+            self.transport.send_request(url, request)
+        }
+    }
+
+    #[test]
+    fn identity_timestamp_example() {
+        let (root_certificate, content_credentials_certificate) = create_test_certificates();
+        let (_, holder) = create_identity_holder(root_certificate.clone());
+        let transport = Arc::new(CountingTransport {
+            transport: LocalTimestampAuthority::new(root_certificate).unwrap(),
+            requests: Mutex::new(0),
+        });
+        let authority =
+            TimestampAuthority::new("http://localhost/tsa".to_string(), Some(transport.clone()));
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.set_timestamp_authority(Some(authority));
+        cc.set_identity_credential_holder(Some(holder));
+        cc.add_created_assertion().unwrap();
+        // The manifest is signed twice, but only the final signature is timestamped.
+        let signed_file = cc.embed_manifest(None).unwrap();
+        assert_eq!(*transport.requests.lock().unwrap(), 1);
+
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        let signer = report.active_manifest.unwrap().signer.unwrap();
+        assert!(signer.signing_time.is_some());
+    }
+
+    #[test]
+    fn training_mining_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let options = CustomAITrainingOptions {
+            ai_training: AIDataMiningUsage::NotAllowed,
+            ai_generative_training: AIDataMiningUsage::NotAllowed,
+            data_mining: AIDataMiningUsage::Constrained {
+                constraint_info: Cow::Borrowed("https://example.com/licensing"),
+            },
+            inference: AIDataMiningUsage::Allowed,
+        };
+
+        for legacy in [false, true] {
+            let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+            let cc = ContentCredentials::new(content_credentials_certificate.clone(), file, None)
+                .unwrap();
+            cc.add_permissive_ai_training_assertions().unwrap();
+            cc.add_custom_ai_training_assertions(options.clone())
+                .unwrap();
+            cc.set_legacy_ai_training_assertions(legacy);
+            let signed_file = cc.embed_manifest(None).unwrap();
+            let report = ContentCredentials::read(signed_file).unwrap();
+            let active_manifest = report.active_manifest.expect("No active manifest");
+            let labels: Vec<&str> = active_manifest
+                .assertions
+                .iter()
+                .map(|assertion| assertion.label.as_str())
+                .collect();
+            if legacy {
+                assert!(labels.contains(&"c2pa.inference"));
+                assert!(!labels.contains(&"c2pa.training-mining"));
+            } else {
+                assert!(labels.contains(&"c2pa.training-mining"));
+                assert!(!labels.contains(&"c2pa.ai_training"));
+            }
+            assert_eq!(
+                active_manifest.get_ai_training_options().unwrap(),
+                Some(options.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn exif_source_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-exif.jpg")), None, None);
+        let exif_data = ExifData::from_file(&file).unwrap();
+        assert_eq!(
+            exif_data.latitude,
+            Some(Latitude {
+                angle: Angle::DegreesMinutesSeconds {
+                    degrees: 39,
+                    minutes: 21,
+                    seconds: 6.12,
+                },
+                reference: LatitudeRef::North,
+            })
+        );
+        assert_eq!(
+            exif_data.timestamp,
+            Some("2019-09-22T18:22:57Z".parse().unwrap())
+        );
+        assert_eq!(exif_data.exposure_time, Some(Rational::new(1, 100)));
+        assert_eq!(exif_data.make.as_deref(), Some("ProofMode"));
+        let error = Latitude::from_decimal(-95.0).unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::ExifValueOutOfRange { .. }));
+        assert_eq!(error.code(), 604);
+
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_exif_assertion_from_source(Some(vec![
+            ExifField::ExposureTime,
+            ExifField::FNumber,
+            ExifField::Make,
+            ExifField::Model,
+        ]))
+        .unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let exif = active_manifest
+            .assertions
+            .iter()
+            .find(|assertion| assertion.label == "stds.exif")
+            .expect("No EXIF assertion");
+        assert!(exif.json.contains("ProofMode In-App Camera v2.0"));
+        assert!(!exif.json.contains("GPSLatitude"));
+    }
+
+    #[test]
+    fn exif_source_out_of_range_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        // A latitude of 39° 21' 60", written as 6000/100 seconds instead of 612/100.
+        let mut bytes = fs::read("tests/media/test-exif.jpg").unwrap();
+        let latitude = [
+            0, 0, 0, 39, 0, 0, 0, 1, 0, 0, 0, 21, 0, 0, 0, 1, 0, 0, 2, 100, 0, 0, 0, 100,
+        ];
+        let offset = bytes
+            .windows(latitude.len())
+            .position(|window| window == latitude)
+            .expect("No GPS latitude");
+        bytes[offset + 16..offset + 20].copy_from_slice(&6000u32.to_be_bytes());
+        let file = FileData::new(None, Some(bytes), Some("test-exif.jpg".to_owned()));
+
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_exif_assertion_from_source(None).unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let exif = active_manifest
+            .assertions
+            .iter()
+            .find(|assertion| assertion.label == "stds.exif")
+            .expect("No EXIF assertion");
+        assert!(!exif.json.contains("GPSLatitude\""));
+        assert!(exif.json.contains("GPSLongitude"));
+        assert!(exif.json.contains("ProofMode"));
+    }
+
+    #[test]
+    fn exif_angle_rounding_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        // Minutes that round up to 60 carry into the degrees.
+        cc.add_exif_assertion(ExifData {
+            latitude: Some(Latitude::from_decimal(39.999999999).unwrap()),
+            longitude: Some(Longitude {
+                angle: Angle::DegreesMinutesSeconds {
+                    degrees: 74,
+                    minutes: 59,
+                    seconds: 59.99999999,
+                },
+                reference: LongitudeRef::West,
+            }),
+            ..ExifData::default()
+        })
+        .unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let exif = active_manifest
+            .assertions
+            .iter()
+            .find(|assertion| assertion.label == "stds.exif")
+            .expect("No EXIF assertion");
+        assert!(exif.json.contains("\"40,0N\""), "{}", exif.json);
+        assert!(exif.json.contains("\"75,0W\""), "{}", exif.json);
+    }
+
+    #[test]
+    fn location_privacy_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-exif.jpg")), None, None);
+        let exif_data = ExifData::from_file(&file).unwrap();
+
+        let sign = |privacy: LocationPrivacy| {
+            let cc = ContentCredentials::new(
+                content_credentials_certificate.clone(),
+                file.clone(),
+                None,
+            )
+            .unwrap();
+            let privacy_report = cc
+                .add_exif_assertion_with_location_privacy(exif_data.clone(), privacy)
+                .unwrap();
+            let signed_file = cc.embed_manifest(None).unwrap();
+            let report = ContentCredentials::read(signed_file).unwrap();
+            let assertions = report.active_manifest.unwrap().assertions;
+            (privacy_report, assertions)
+        };
+        let get_json = |assertions: &[AssertionInfo], label: &str| {
+            assertions
+                .iter()
+                .find(|assertion| assertion.label == label)
+                .map(|assertion| assertion.json.clone())
+        };
+
+        let (privacy_report, assertions) = sign(LocationPrivacy::Remove);
+        assert_eq!(privacy_report.removed_fields.len(), 13);
+        assert!(privacy_report
+            .removed_fields
+            .contains(&"exif:GPSLatitude".to_string()));
+        let exif = get_json(&assertions, "stds.exif").unwrap();
+        assert!(!exif.contains("exif:GPS"));
+        assert!(exif.contains("ProofMode"));
+
+        let (privacy_report, assertions) = sign(LocationPrivacy::Coarsen { precision: 0.1 });
+        assert_eq!(
+            privacy_report.coarsened_fields,
+            vec![
+                "exif:GPSLatitude".to_string(),
+                "exif:GPSLongitude".to_string(),
+                "exif:GPSHPositioningError".to_string(),
+            ]
+        );
+        let exif = get_json(&assertions, "stds.exif").unwrap();
+        assert!(exif.contains("39,24N"));
+        assert!(exif.contains("74,24W"));
+
+        let (privacy_report, assertions) = sign(LocationPrivacy::SeparateAssertion);
+        assert_eq!(
+            privacy_report.location_assertion_label.as_deref(),
+            Some(LOCATION_LABEL)
+        );
+        let exif = get_json(&assertions, "stds.exif").unwrap();
+        assert!(!exif.contains("exif:GPS"));
+        let location = get_json(&assertions, LOCATION_LABEL).unwrap();
+        assert!(location.contains("exif:GPSLatitude"));
+
+        let error = ContentCredentials::new(content_credentials_certificate, file, None)
+            .unwrap()
+            .add_exif_assertion_with_location_privacy(
+                exif_data,
+                LocationPrivacy::Coarsen { precision: 0.0 },
+            )
+            .unwrap_err();
+        assert_eq!(error.code(), 700);
+    }
+
+    #[test]
+    fn redaction_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-exif.jpg")), None, None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate.clone(), file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        cc.add_exif_assertion_from_source(None).unwrap();
+        cc.add_email_assertion(
+            "jane@example.com".to_string(),
+            "Jane Doe".to_string(),
+            false,
+        )
+        .unwrap();
+        let original_file = cc.embed_manifest(None).unwrap();
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_ingredient(original_file, Relationship::ParentOf)
+            .unwrap();
+        let error = cc
+            .redact_parent_assertions(vec!["c2pa.actions".to_string()], None)
+            .unwrap_err();
+        assert!(matches!(error, SimpleC2PAError::InvalidRedaction { .. }));
+        assert_eq!(error.code(), 605);
+        let error = cc
+            .redact_parent_assertions(vec!["stds.iptc".to_string()], None)
+            .unwrap_err();
+        assert_eq!(error.code(), 605);
+        let uris = cc
+            .redact_parent_assertions(
+                vec![
+                    "stds.exif".to_string(),
+                    "stds.schema-org.CreativeWork".to_string(),
+                ],
+                Some("c2pa.PII.present".to_string()),
+            )
+            .unwrap();
+        assert_eq!(uris.len(), 2);
+        assert!(uris[0].ends_with("/c2pa.assertions/stds.exif"));
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let output_path = output_dir.path().join("c2pa-redaction-test-1.jpg");
+        let signed_file = cc.embed_manifest(Some(output_path)).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        let active_manifest = report.active_manifest.expect("No active manifest");
+        let actions = active_manifest
+            .assertions
+            .iter()
+            .find(|assertion| assertion.label == "c2pa.actions")
+            .expect("No actions assertion");
+        assert!(actions.json.contains("c2pa.redacted"));
+        assert!(actions.json.contains("c2pa.PII.present"));
+        let parent_manifest = report
+            .manifests
+            .iter()
+            .find(|manifest| manifest.label != active_manifest.label)
+            .expect("No parent manifest");
+        assert!(parent_manifest
+            .assertions
+            .iter()
+            .all(|assertion| assertion.label != "stds.exif"));
+    }
+
+    fn get_wav_bytes() -> Vec<u8> {
+        let samples: Vec<u8> = (0..8000u32)
+            .flat_map(|index| (((index % 100) as i16 - 50) * 300).to_le_bytes())
+            .collect();
+        let mut wav = b"RIFF".to_vec();
+        wav.extend((36 + samples.len() as u32).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(8000u32.to_le_bytes());
+        wav.extend(16000u32.to_le_bytes());
+        wav.extend(2u16.to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend((samples.len() as u32).to_le_bytes());
+        wav.extend(samples);
+        wav
+    }
+
+    #[test]
+    fn media_format_example() {
+        let headers: Vec<(&[u8], Option<&str>, &str)> = vec![
+            (b"\xff\xd8\xff\xe0", None, "image/jpeg"),
+            (b"\x89PNG\r\n\x1a\n", Some("photo.jpg"), "image/png"),
+            (b"RIFF\0\0\0\0WEBPVP8 ", None, "image/webp"),
+            (b"RIFF\0\0\0\0WAVEfmt ", None, "audio/wav"),
+            (b"RIFF\0\0\0\0AVI LIST", None, "video/x-msvideo"),
+            (b"\0\0\0\x18ftypheic", None, "image/heic"),
+            (b"\0\0\0\x18ftypmif1", Some("photo.heic"), "image/heic"),
+            (b"\0\0\0\x1cftypavif", None, "image/avif"),
+            (b"\0\0\0\x18ftypisom", None, "video/mp4"),
+            (b"\0\0\0\x18ftypisom", Some("voice.m4a"), "audio/mp4"),
+            (b"\0\0\0\x14ftypqt  ", None, "video/quicktime"),
+            (b"II*\0", None, "image/tiff"),
+            (b"II*\0", Some("raw.DNG"), "image/x-adobe-dng"),
+            (b"ID3\x04\0", None, "audio/mpeg"),
+            (b"\xff\xfb\x90\x64", None, "audio/mpeg"),
+            (b"<?xml version=\"1.0\"?><svg>", None, "image/svg+xml"),
+            (b"\xff\xfe<\0s\0v\0g\0>\0", None, "image/svg+xml"),
+            (b"unrecognized", Some("song.mp3"), "audio/mpeg"),
+        ];
+        for (header, file_name, mime_type) in headers {
+            let file = FileData::new(None, Some(header.to_vec()), file_name.map(str::to_owned));
+            assert_eq!(file.get_mime_type().unwrap(), mime_type);
+        }
+        let file = FileData::new(None, Some(b"unrecognized".to_vec()), None);
+        let error = file.get_mime_type().unwrap_err();
+        assert_eq!(error.code(), 300);
+        assert!(error.to_string().contains("video/mp4"));
+        // The byte order mark of UTF-16LE markup is also a frame sync of MPEG audio.
+        let file = FileData::new(None, Some(b"\xff\xfe<\0p\0>\0".to_vec()), None);
+        assert_eq!(file.get_mime_type().unwrap_err().code(), 300);
+
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"8\" height=\"8\">\
+            <rect width=\"8\" height=\"8\"/></svg>"
+            .to_vec();
+        let mut files = vec![
+            (
+                FileData::new(None, Some(get_wav_bytes()), None),
+                "audio/wav",
+            ),
+            (FileData::new(None, Some(svg), None), "image/svg+xml"),
+        ];
+        let fixtures = [
+            ("test-1.png", "image/png"),
+            ("test-1.webp", "image/webp"),
+            ("test-1.heic", "image/heic"),
+            ("test-1.avif", "image/avif"),
+            ("test-1.tif", "image/tiff"),
+            ("test-1.dng", "image/x-adobe-dng"),
+            ("test-1.mp4", "video/mp4"),
+            ("test-1.mov", "video/quicktime"),
+            ("test-1.mp3", "audio/mpeg"),
+        ];
+        for (file_name, mime_type) in fixtures {
+            let path = PathBuf::from("tests/media").join(file_name);
+            files.push((FileData::new(Some(path), None, None), mime_type));
+        }
+        for (file, mime_type) in files {
+            let cc = ContentCredentials::new(content_credentials_certificate.clone(), file, None)
+                .unwrap();
+            cc.add_created_assertion().unwrap();
+            let signed_file = cc.embed_manifest(None).unwrap();
+            assert_eq!(signed_file.get_mime_type().unwrap(), mime_type);
+            let report = ContentCredentials::read(signed_file).unwrap();
+            assert!(report.is_valid, "{:?}", report.validation_statuses);
+            let active_manifest = report.active_manifest.unwrap();
+            assert_eq!(active_manifest.format, mime_type);
+            // Thumbnails are generated from the images the `image` crate decodes.
+            assert_eq!(
+                active_manifest.thumbnail_format.is_some(),
+                ["image/png", "image/webp", "image/tiff"].contains(&mime_type),
+                "{}",
+                mime_type
+            );
+        }
+    }
+
+    #[test]
+    fn fragmented_recording_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let init_segment = FileData::new(
+            Some(PathBuf::from("tests/media/fragmented/init.mp4")),
+            None,
+            None,
+        );
+        assert_eq!(init_segment.get_mime_type().unwrap(), "video/mp4");
+
+        let cc =
+            ContentCredentials::new(content_credentials_certificate, init_segment, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path().join("c2pa-fragmented");
+        let recording = FragmentedRecording::new(cc.clone(), output_dir.clone());
+        assert_eq!(
+            FragmentedRecording::get_signed_dir(output_dir.clone()).unwrap(),
+            None
+        );
+        let mut signed_dir = PathBuf::new();
+        for index in 1..=3 {
+            let fragment = PathBuf::from(format!("tests/media/fragmented/segment-{}.m4s", index));
+            let signed_fragment = recording
+                .add_fragment(fragment.clone())
+                .unwrap()
+                .expect("The recording was not signed");
+            signed_dir = FragmentedRecording::get_signed_dir(output_dir.clone())
+                .unwrap()
+                .expect("No signed recording");
+            assert_eq!(
+                signed_fragment.get_path(),
+                Some(signed_dir.join(format!("segment-{}.m4s", index)))
+            );
+            assert_ne!(
+                signed_fragment.get_bytes().unwrap(),
+                fs::read(&fragment).unwrap()
+            );
+            assert_eq!(recording.get_fragments().len(), index);
+            // Only the current signed recording is kept.
+            let signed_dirs = fs::read_dir(&output_dir)
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().path().is_dir())
+                .count();
+            assert_eq!(signed_dirs, 1);
+
+            // Every fragment so far is valid against the signed init segment on its own.
+            for signed_index in 1..=index {
+                let fragments = vec![signed_dir.join(format!("segment-{}.m4s", signed_index))];
+                let report = ManifestReader::with_fragments(signed_dir.join("init.mp4"), fragments)
+                    .unwrap()
+                    .get_report()
+                    .unwrap();
+                assert!(report.is_valid, "{:?}", report.validation_statuses);
+                assert!(report.active_manifest.is_some());
+            }
+        }
+
+        let error = cc
+            .embed_manifest_fragmented(vec![signed_dir.join("segment-1.m4s")], signed_dir)
+            .unwrap_err();
+        assert_eq!(error.code(), 700);
+
+        // A recording signed every other fragment leaves the last one to `sign`.
+        let output_dir = temp_dir.path().join("c2pa-fragmented-interval");
+        let recording = FragmentedRecording::new_with_interval(cc.clone(), output_dir.clone(), 2);
+        let fragments = (1..=3)
+            .map(|index| PathBuf::from(format!("tests/media/fragmented/segment-{}.m4s", index)))
+            .collect::<Vec<_>>();
+        assert!(recording
+            .add_fragment(fragments[0].clone())
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            FragmentedRecording::get_signed_dir(output_dir.clone()).unwrap(),
+            None
+        );
+        assert!(recording
+            .add_fragment(fragments[1].clone())
+            .unwrap()
+            .is_some());
+        assert!(recording
+            .add_fragment(fragments[2].clone())
+            .unwrap()
+            .is_none());
+        assert_eq!(recording.get_fragments(), fragments);
+        let signed_dir = FragmentedRecording::get_signed_dir(output_dir.clone())
+            .unwrap()
+            .unwrap();
+        assert!(!signed_dir.join("segment-3.m4s").exists());
+        let signed_files = recording.sign().unwrap();
+        assert_eq!(signed_files.len(), 4);
+        let signed_dir = FragmentedRecording::get_signed_dir(output_dir.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            signed_files[0].get_path(),
+            Some(signed_dir.join("init.mp4"))
+        );
+        let signed_fragments = signed_files[1..]
+            .iter()
+            .map(|file| file.get_path().unwrap())
+            .collect::<Vec<_>>();
+        let report = ManifestReader::with_fragments(signed_dir.join("init.mp4"), signed_fragments)
+            .unwrap()
+            .get_report()
+            .unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+
+        // Signing in one go swaps the whole recording in, like a recording.
+        let output_dir = temp_dir.path().join("c2pa-fragmented-once");
+        let signed_files = cc
+            .embed_manifest_fragmented(fragments.clone(), output_dir.clone())
+            .unwrap();
+        let signed_dir = FragmentedRecording::get_signed_dir(output_dir.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            signed_files[0].get_path(),
+            Some(signed_dir.join("init.mp4"))
+        );
+        cc.embed_manifest_fragmented(fragments, output_dir.clone())
+            .unwrap();
+        let signed_dirs = fs::read_dir(&output_dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().is_dir())
+            .count();
+        assert_eq!(signed_dirs, 1);
+        assert_ne!(
+            FragmentedRecording::get_signed_dir(output_dir.clone()).unwrap(),
+            Some(signed_dir)
+        );
+
+        let image = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(
+            create_content_credentials_certificate(None, None, None).unwrap(),
+            image,
+            None,
+        )
+        .unwrap();
+        let error = cc
+            .embed_manifest_fragmented(vec![], output_dir)
+            .unwrap_err();
+        assert_eq!(error.code(), 300);
+    }
+
+    #[test]
+    fn thumbnail_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+        let png_options = ThumbnailOptions {
+            max_size: 64,
+            format: ThumbnailFormat::Png,
+            ..ThumbnailOptions::default()
+        };
+        let thumbnails = vec![
+            (None, Some("image/jpeg")),
+            (Some(Thumbnail::Generated(png_options)), Some("image/png")),
+            (
+                Some(Thumbnail::Custom(
+                    fs::read("tests/media/test-1.png").unwrap(),
+                )),
+                Some("image/png"),
+            ),
+            (Some(Thumbnail::None), None),
+        ];
+        for (thumbnail, thumbnail_format) in thumbnails {
+            let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+            let cc = ContentCredentials::new(content_credentials_certificate.clone(), file, None)
+                .unwrap();
+            cc.add_created_assertion().unwrap();
+            if let Some(thumbnail) = thumbnail {
+                cc.set_thumbnail(thumbnail.clone()).unwrap();
+                cc.set_ingredient_thumbnails(thumbnail).unwrap();
+            }
+            let signed_file = cc.embed_manifest(None).unwrap();
+
+            let report = ContentCredentials::read(signed_file).unwrap();
+            assert!(report.is_valid, "{:?}", report.validation_statuses);
+            let active_manifest = report.active_manifest.unwrap();
+            assert_eq!(
+                active_manifest.thumbnail_format.as_deref(),
+                thumbnail_format
+            );
+        }
+
+        // An ingredient without a thumbnail keeps its manifest store.
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate.clone(), file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.png")), None, None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate.clone(), file, None).unwrap();
+        let instance_id = cc
+            .add_ingredient(signed_file, Relationship::ParentOf)
+            .unwrap();
+        cc.set_ingredient_thumbnail(Some(instance_id), Thumbnail::None)
+            .unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.is_valid, "{:?}", report.validation_statuses);
+        assert_eq!(report.manifests.len(), 2);
+        let ingredient = &report.active_manifest.unwrap().ingredients[0];
+        assert!(ingredient.thumbnail_uri.is_none());
+        let ingredient_manifest = ingredient.active_manifest.as_ref().unwrap();
+        assert!(report
+            .manifests
+            .iter()
+            .any(|manifest| manifest.label.as_ref() == Some(ingredient_manifest)));
+
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate.clone(), file, None).unwrap();
+        cc.set_ingredient_thumbnail(None, Thumbnail::None).unwrap();
+        let error = cc
+            .set_ingredient_thumbnail(Some("xmp:iid:unknown".to_owned()), Thumbnail::None)
+            .unwrap_err();
+        assert_eq!(error.code(), 700);
+
+        let error = cc
+            .set_thumbnail(Thumbnail::Custom(get_wav_bytes()))
+            .unwrap_err();
+        assert_eq!(error.code(), 700);
+        let invalid_options = [
+            ThumbnailOptions {
+                quality: 0,
+                ..ThumbnailOptions::default()
+            },
+            ThumbnailOptions {
+                quality: 101,
+                ..ThumbnailOptions::default()
+            },
+            ThumbnailOptions {
+                max_size: 0,
+                ..ThumbnailOptions::default()
+            },
+        ];
+        for options in invalid_options {
+            let thumbnail = Thumbnail::Generated(options);
+            let error = cc.set_thumbnail(thumbnail.clone()).unwrap_err();
+            assert_eq!(error.code(), 700);
+            let error = cc.set_ingredient_thumbnails(thumbnail.clone()).unwrap_err();
+            assert_eq!(error.code(), 700);
+            let error = cc.set_ingredient_thumbnail(None, thumbnail).unwrap_err();
+            assert_eq!(error.code(), 700);
+        }
+        cc.add_created_assertion().unwrap();
+        cc.embed_manifest(None).unwrap();
+
+        // A video gets no thumbnail by default, but one cannot be generated from it.
+        let file = FileData::new(Some(PathBuf::from("tests/media/test-1.mp4")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, file, None).unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_file = cc.embed_manifest(None).unwrap();
+        let report = ContentCredentials::read(signed_file).unwrap();
+        assert!(report.active_manifest.unwrap().thumbnail_format.is_none());
+        cc.set_thumbnail(Thumbnail::default()).unwrap();
+        assert_eq!(cc.embed_manifest(None).unwrap_err().code(), 300);
+    }
+
+    #[test]
+    fn resources_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let original = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate.clone(), original, None)
+            .unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_original = cc.embed_manifest(None).unwrap();
+
+        let edited = FileData::new(Some(PathBuf::from("tests/media/test-1.png")), None, None);
+        let cc = ContentCredentials::new(content_credentials_certificate, edited, None).unwrap();
+        cc.add_ingredient(signed_original, Relationship::ParentOf)
+            .unwrap();
+        cc.add_placed_assertion().unwrap();
+        let signed_edit = cc.embed_manifest(None).unwrap();
+
+        let reader = ManifestReader::new(signed_edit).unwrap();
+        let active_manifest = reader.get_report().unwrap().active_manifest.unwrap();
+        let thumbnail_uri = active_manifest.thumbnail_uri.unwrap();
+        let thumbnail = reader.get_resource(thumbnail_uri.clone()).unwrap();
+        assert_eq!(thumbnail.uri, thumbnail_uri);
+        assert_eq!(thumbnail.mime_type, "image/jpeg");
+        assert_eq!(Some(thumbnail.manifest_label), active_manifest.label);
+        assert!(thumbnail.bytes.starts_with(&[0xff, 0xd8, 0xff]));
+
+        assert_eq!(active_manifest.ingredients.len(), 1);
+        let parent = &active_manifest.ingredients[0];
+        assert_eq!(parent.relationship, "parentOf");
+        assert!(parent.active_manifest.is_some());
+        let parent_thumbnail = reader
+            .get_resource(parent.thumbnail_uri.clone().unwrap())
+            .unwrap();
+        assert_eq!(
+            Some(parent_thumbnail.mime_type),
+            parent.thumbnail_format.clone()
+        );
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output_dir = temp_dir.path().join("c2pa-resources");
+        let files = reader.write_resources(output_dir.clone()).unwrap();
+        assert_eq!(files.len(), reader.get_resource_uris().len());
+        for file in files {
+            let path = file.get_path().unwrap();
+            assert!(path.starts_with(&output_dir));
+            assert!(path.extension().is_some());
+            assert!(!file.get_bytes().unwrap().is_empty());
+        }
+
+        let error = reader
+            .get_resource(
+                "self#jumbf=c2pa/urn:uuid:unknown/c2pa.assertions/c2pa.thumbnail.claim.jpeg"
+                    .to_owned(),
+            )
+            .unwrap_err();
+        assert_eq!(error.code(), 503);
+    }
+
+    #[test]
+    fn provenance_example() {
+        let (_, content_credentials_certificate) = create_test_certificates();
+
+        let capture = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let camera = ApplicationInfo::new("Camera".to_owned(), "1.0".to_owned(), None);
+        let cc = ContentCredentials::new(
+            content_credentials_certificate.clone(),
+            capture,
+            Some(camera),
+        )
+        .unwrap();
+        cc.add_created_assertion().unwrap();
+        let signed_capture = cc.embed_manifest(None).unwrap();
+
+        let crop = FileData::new(Some(PathBuf::from("tests/media/test-1.png")), None, None);
+        let editor = ApplicationInfo::new("Editor".to_owned(), "2.0".to_owned(), None);
+        let cc =
+            ContentCredentials::new(content_credentials_certificate.clone(), crop, Some(editor))
+                .unwrap();
+        let instance_id = cc
+            .add_ingredient(signed_capture, Relationship::ParentOf)
+            .unwrap();
+        let mut cropped = ActionData::new(ActionType::Cropped);
+        cropped.software_agent = Some("Editor".to_owned());
+        cropped.ingredient_instance_id = Some(instance_id);
+        cc.add_action(cropped).unwrap();
+        let signed_crop = cc.embed_manifest(None).unwrap();
+
+        let publication = FileData::new(
+            None,
+            Some(fs::read("tests/media/test-exif.jpg").unwrap()),
+            Some("published.jpg".to_owned()),
+        );
+        let cc =
+            ContentCredentials::new(content_credentials_certificate, publication, None).unwrap();
+        cc.add_ingredient(signed_crop, Relationship::ParentOf)
+            .unwrap();
+        cc.add_action(ActionData::new(ActionType::Published))
+            .unwrap();
+        let signed_publication = cc.embed_manifest(None).unwrap();
+
+        let provenance = ContentCredentials::read_provenance(signed_publication).unwrap();
+        assert!(provenance.is_valid, "{:?}", provenance.validation_statuses);
+        assert!(provenance.relationship.is_none());
+        let history = provenance.get_history();
+        assert_eq!(history.len(), 3);
+        assert!(history[0]
+            .claim_generator
+            .as_ref()
+            .unwrap()
+            .starts_with("Camera/"));
+        assert!(history[1]
+            .claim_generator
+            .as_ref()
+            .unwrap()
+            .starts_with("Editor/"));
+        assert!(history[2]
+            .claim_generator
+            .as_ref()
+            .unwrap()
+            .starts_with("Simple-C2PA/"));
+        assert_eq!(history[0].actions[0].action, "c2pa.created");
+        assert_eq!(history[1].actions[0].action, "c2pa.cropped");
+        assert_eq!(
+            history[1].actions[0].software_agent.as_deref(),
+            Some("Editor")
+        );
+        assert_eq!(
+            history[1].actions[0].ingredient_instance_id.as_deref(),
+            Some(history[0].instance_id.as_str())
+        );
+        assert_eq!(history[2].actions[0].action, "c2pa.published");
+        for node in &history[..2] {
+            assert_eq!(node.relationship.as_deref(), Some("parentOf"));
+            assert!(node.manifest_label.is_some());
+            assert!(node.signer.is_some());
+            assert!(node.is_valid, "{:?}", node.validation_statuses);
+        }
+
+        let json = serde_json::to_value(&provenance).unwrap();
+        assert_eq!(json["ingredients"][0]["relationship"], "parentOf");
+        assert_eq!(
+            json["ingredients"][0]["ingredients"][0]["actions"][0]["action"],
+            "c2pa.created"
+        );
+
+        let unsigned = FileData::new(Some(PathBuf::from("tests/media/test-1.jpg")), None, None);
+        let error = ContentCredentials::read_provenance(unsigned).unwrap_err();
+        assert_eq!(error.code(), 500);
+    }
 }